@@ -0,0 +1,331 @@
+//! The windowed main loop, for the demo and any other tool built on the same setup.
+//!
+//! `run` opens the window, loads the assets, bakes the fonts and then drives an `App`: it
+//! translates window events into Nuklear input, follows resizes, hidpi changes and zoom, reloads
+//! changed assets with `--watch`, paces frames and draws what the app laid out. An app only has
+//! to lay out its windows in `update`.
+
+use gfx;
+use gfx::Device;
+use gfx_window_glutin;
+use glutin;
+use glutin::dpi::LogicalSize;
+use glutin::GlRequest;
+use nuklear::{Context, Vec2};
+
+use std::fs::read;
+use std::path::{Path, PathBuf};
+
+use assets::{self, AssetError, Manifest};
+use clipboard::{self, ClipboardProvider, MemoryClipboard, SystemClipboard};
+use fonts::{Family, Glyphs};
+use hot_reload;
+use input::InputTranslator;
+use pacing;
+use record;
+use skin;
+use zoom;
+use {arg_value, init_ui, ColorFormat, DepthFormat, Media, Sources, CLEAR_COLOR};
+
+/// A UI driven by `run`. Every method but `update` defaults to doing nothing.
+pub trait App {
+    /// Called once before the first frame, after the skin is applied. `size` is the UI's logical
+    /// size, the window's divided by the zoom.
+    fn init(&mut self, _ctx: &mut Context, _media: &mut Media, _size: Vec2) {}
+
+    /// Lays out one frame.
+    fn update(&mut self, ctx: &mut Context, media: &mut Media);
+
+    /// Sees every window event before the UI does. Returning true keeps the event from the UI and
+    /// the zoom shortcuts; closing, resizing and hidpi changes are followed regardless.
+    fn on_event(&mut self, _event: &glutin::WindowEvent) -> bool {
+        false
+    }
+
+    /// Called once after the window is closed, while the UI is still alive.
+    fn shutdown(&mut self, _ctx: &mut Context) {}
+}
+
+/// How `run` sets up the window, from the command line or by hand.
+pub struct Settings {
+    pub title: ::std::string::String,
+    /// Initial window size in logical pixels.
+    pub size: (f64, f64),
+    pub asset_root: PathBuf,
+    pub zoom: f64,
+    /// A font baked behind every family for text the manifest's fonts cannot show.
+    pub fallback_font: Option<PathBuf>,
+    pub fallback_glyphs: Vec<Glyphs<'static>>,
+    /// Sample text whose characters the fallback font supplies, on top of `fallback_glyphs`.
+    pub fallback_text: Option<::std::string::String>,
+    pub skin: Option<PathBuf>,
+    pub watch: bool,
+    /// Where to record the input as a replay script.
+    pub record: Option<PathBuf>,
+    pub pacing: pacing::Mode,
+    /// Print the icon atlas statistics at startup.
+    pub atlas_stats: bool,
+}
+
+impl Settings {
+    /// Reads the window options out of `args`: `--asset-root`, `--zoom`, `--fallback-font`,
+    /// `--fallback-glyphs`, `--fallback-text`, `--skin`, `--watch`, `--record`, `--fps`,
+    /// `--on-demand` and `--atlas-stats`. A malformed value is an error naming the option and what
+    /// it expects.
+    pub fn from_args(title: &str, args: &[::std::string::String]) -> Result<Settings, ::std::string::String> {
+        let asset_root = assets::root(arg_value(args, "--asset-root").map(Path::new));
+        let fallback_text = arg_value(args, "--fallback-text").map(str::to_string);
+        let default_glyphs = if fallback_text.is_some() { "" } else { "chinese" };
+        let fallback_glyphs = match arg_value(args, "--fallback-glyphs").unwrap_or(default_glyphs) {
+            "" => Vec::new(),
+            list => Glyphs::parse_list(list).map_err(|e| format!("--fallback-glyphs: {}", e))?,
+        };
+        let skin = if args.iter().any(|a| a == "--skin") {
+            Some(arg_value(args, "--skin").filter(|p| !p.starts_with("--")).map_or_else(|| asset_root.join(skin::GWEN), PathBuf::from))
+        } else {
+            None
+        };
+        let pacing = if args.iter().any(|a| a == "--on-demand") {
+            pacing::Mode::OnDemand
+        } else {
+            let fps = match arg_value(args, "--fps") {
+                Some(n) => n.parse().map_err(|_| format!("--fps expects a frame rate, not '{}'", n))?,
+                None => pacing::DEFAULT_FPS,
+            };
+            pacing::Mode::Continuous { fps }
+        };
+        let zoom = match arg_value(args, "--zoom") {
+            Some(z) => z.parse().map_err(|_| format!("--zoom expects a scale factor, not '{}'", z))?,
+            None => 1.0,
+        };
+
+        Ok(Settings {
+            title: title.to_string(),
+            size: (1280., 800.),
+            zoom,
+            fallback_font: arg_value(args, "--fallback-font").map(PathBuf::from),
+            fallback_glyphs,
+            fallback_text,
+            skin,
+            watch: args.iter().any(|a| a == "--watch"),
+            record: arg_value(args, "--record").map(PathBuf::from),
+            pacing,
+            atlas_stats: args.iter().any(|a| a == "--atlas-stats"),
+            asset_root,
+        })
+    }
+}
+
+/// Opens the window described by `settings` and runs `app` in it until the window is closed.
+/// Fails before the first frame if an asset the window cannot start without, the manifest, a font
+/// or the skin, does not load, or if the input recording cannot be created.
+pub fn run<A: App>(mut app: A, settings: &Settings) -> Result<(), AssetError> {
+    let gl_version = GlRequest::GlThenGles {
+        opengles_version: (2, 0),
+        opengl_version: (3, 3),
+    };
+
+    let (width, height) = settings.size;
+    let builder = glutin::WindowBuilder::new().with_title(settings.title.as_str()).with_dimensions(LogicalSize { width, height });
+
+    let context = glutin::ContextBuilder::new().with_gl(gl_version).with_vsync(true).with_srgb(false).with_depth_buffer(24);
+    let mut event_loop = glutin::EventsLoop::new();
+    let (window, mut device, mut factory, main_color, mut main_depth) = gfx_window_glutin::init::<ColorFormat, DepthFormat>(builder, context, &event_loop).unwrap();
+    let mut encoder: gfx::Encoder<_, _> = factory.create_command_buffer().into();
+
+    let mut hidpi = window.get_hidpi_factor();
+    let mut zoom = zoom::Zoom::new(settings.zoom);
+    let manifest_path = settings.asset_root.join(assets::MANIFEST);
+    let manifest = Manifest::load(&manifest_path)?;
+    let font_files = manifest.read_fonts()?;
+    let fallback_ttf = match settings.fallback_font {
+        Some(ref path) => Some(read(path).map_err(|e| AssetError::io(path, e))?),
+        None => None,
+    };
+    let mut fallback_glyphs = settings.fallback_glyphs.clone();
+    fallback_glyphs.extend(settings.fallback_text.as_ref().map(|text| Glyphs::Text(text)));
+    let fallback = fallback_ttf.as_ref().map(|ttf| Family {
+        name: "Fallback",
+        ttf,
+        glyphs: &fallback_glyphs,
+        sizes: &[],
+        fallbacks: &[],
+    });
+    let mut sources = Sources {
+        manifest_path,
+        manifest,
+        font_files,
+        fallback,
+        skin: settings.skin.clone(),
+    };
+    let families = sources.families()?;
+    let (mut drawer, mut ctx, mut media, mut config) = init_ui(&mut factory, main_color, &sources.manifest, &families, hidpi * zoom.factor())?;
+
+    if settings.atlas_stats {
+        match media.icons.atlas_stats() {
+            Some(stats) => println!("{}", stats),
+            None => println!("no icon atlas"),
+        }
+    }
+
+    if let Some(ref path) = sources.skin {
+        let skin = skin::Skin::load(&mut factory, &mut drawer, path)?;
+        skin.apply(&mut ctx);
+        media.skin = Some(skin);
+    }
+
+    let watcher = if settings.watch {
+        let asset_root = &settings.asset_root;
+        let mut roots = vec![asset_root.clone()];
+        roots.extend(sources.skin.as_ref().and_then(|p| p.parent()).filter(|dir| !dir.starts_with(asset_root)).map(Path::to_path_buf));
+        Some(hot_reload::Watcher::spawn(roots, event_loop.create_proxy()))
+    } else {
+        None
+    };
+
+    let provider: Box<dyn ClipboardProvider> = match SystemClipboard::for_window(&window) {
+        Ok(system) => Box::new(system),
+        Err(e) => {
+            eprintln!("{}, copy and paste will stay inside the window", e);
+            Box::new(MemoryClipboard::default())
+        }
+    };
+    let _clipboard = clipboard::install(&mut ctx, provider);
+
+    let mut input = InputTranslator::default();
+    input.set_zoom(zoom.factor());
+
+    let mut recorder = match settings.record {
+        Some(ref path) => Some(record::Recorder::create(path).map_err(|error| AssetError::Record { path: path.clone(), error })?),
+        None => None,
+    };
+    let mut frame = 0;
+
+    let mut pacer = pacing::FramePacer::new(settings.pacing);
+
+    let LogicalSize { width, height } = window.get_inner_size().unwrap();
+    app.init(&mut ctx, &mut media, Vec2 { x: (width / zoom.factor()) as f32, y: (height / zoom.factor()) as f32 });
+
+    let mut closed = false;
+    while !closed {
+        let mut resized = false;
+        let mut rescaled = false;
+
+        ctx.input_begin();
+        pacer.wait_events(&mut event_loop, |event| {
+            if let glutin::Event::WindowEvent { event, .. } = event {
+                let consumed = app.on_event(&event);
+                // What a replay needs to reach the same state: the resizes and the UI's input, but
+                // not what the app or the zoom shortcuts took.
+                let replayed = match event {
+                    glutin::WindowEvent::CloseRequested => {
+                        closed = true;
+                        false
+                    }
+                    glutin::WindowEvent::Resized(_) => {
+                        resized = true;
+                        true
+                    }
+                    glutin::WindowEvent::HiDpiFactorChanged(factor) => {
+                        hidpi = factor;
+                        resized = true;
+                        rescaled = true;
+                        false
+                    }
+                    _ if consumed => false,
+                    glutin::WindowEvent::KeyboardInput { input: ref key, .. } if zoom::is_shortcut(key) => {
+                        rescaled |= zoom.handle_key(key);
+                        false
+                    }
+                    ref event => {
+                        input.handle(&mut ctx, event);
+                        true
+                    }
+                };
+                if let (true, Some(recorder)) = (replayed, recorder.as_mut()) {
+                    recorder.record(frame, &event).expect("cannot write input recording");
+                }
+            }
+        });
+        ctx.input_end();
+
+        if let Some(ref mut recorder) = recorder {
+            recorder.zoom(frame, zoom.factor()).expect("cannot write input recording");
+        }
+
+        if closed {
+            break;
+        }
+
+        pacing::set_delta_time(&mut ctx, pacer.delta());
+
+        if resized {
+            window.resize(window.get_inner_size().unwrap().to_physical(hidpi));
+            let mut main_color = drawer.col.clone().unwrap();
+            gfx_window_glutin::update_views(&window, &mut main_color, &mut main_depth);
+            drawer.col = Some(main_color);
+        }
+        if rescaled {
+            let rescaled = sources.families().map_err(|e| e.to_string()).and_then(|families| media.rescale(&mut factory, &mut drawer, &mut ctx, &mut config, &sources.manifest, &families, hidpi * zoom.factor()));
+            if let Err(e) = rescaled {
+                eprintln!("{}, keeping the current fonts", e);
+            }
+            input.set_zoom(zoom.factor());
+        }
+        if let Some(ref watcher) = watcher {
+            let changed = watcher.changed();
+            let reload = hot_reload::classify(&changed, &sources.manifest_path, &sources.manifest, sources.skin.as_deref());
+            if !reload.is_empty() {
+                media.reload(&mut factory, &mut drawer, &mut ctx, &mut config, &mut sources, &reload, hidpi * zoom.factor());
+            }
+        }
+
+        // The UI is laid out in logical pixels divided by the zoom, the space the input translator
+        // maps window events to; the drawer scales it to the physical framebuffer.
+        let LogicalSize { width, height } = window.get_inner_size().unwrap();
+        let (width, height) = (width / zoom.factor(), height / zoom.factor());
+        let scale = Vec2 { x: (hidpi * zoom.factor()) as f32, y: (hidpi * zoom.factor()) as f32 };
+
+        app.update(&mut ctx, &mut media);
+
+        encoder.clear(drawer.col.as_ref().unwrap(), CLEAR_COLOR);
+        drawer.draw(&mut ctx, &mut config, &mut encoder, &mut factory, width as u32, height as u32, scale);
+        encoder.flush(&mut device);
+        window.swap_buffers().unwrap();
+        // Deletes the GL objects of freed textures, and of the handles this frame let go.
+        device.cleanup();
+
+        if let Some(ref mut recorder) = recorder {
+            recorder.end_frame().expect("cannot write input recording");
+        }
+        frame += 1;
+
+        pacer.end_frame(&ctx);
+        ctx.clear();
+    }
+
+    app.shutdown(&mut ctx);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(args: &[&str]) -> Result<Settings, ::std::string::String> {
+        let args: Vec<_> = args.iter().map(|a| a.to_string()).collect();
+        Settings::from_args("test", &args)
+    }
+
+    #[test]
+    fn malformed_options_are_errors() {
+        let good = settings(&["t", "--zoom", "1.5", "--fps", "30", "--fallback-glyphs", "latin, greek"]).unwrap();
+        assert_eq!(good.zoom, 1.5);
+        assert_eq!(good.fallback_glyphs.len(), 2);
+
+        for args in &[&["t", "--zoom", "big"][..], &["t", "--fps", "-1"], &["t", "--fallback-glyphs", "latin,klingon"]] {
+            let e = settings(args).err().unwrap_or_else(|| panic!("{:?}", args));
+            assert!(e.starts_with(args[1]), "{}", e);
+        }
+    }
+}
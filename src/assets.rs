@@ -0,0 +1,430 @@
+//! The asset manifest: icons, images and fonts by logical name.
+//!
+//! The manifest is a `skin_file` document with an `[icons]`, an `[images]` and a `[fonts]`
+//! section mapping names to files relative to the manifest. Icons and images are uploaded into a
+//! `Registry` the demos look up by name; images also keep their manifest order, which the image
+//! gallery shows them in. Fonts are read as bytes for the font families to bake.
+//!
+//! Image files are recognized by their content: PNG, JPEG, GIF (its first frame), BMP, ICO and
+//! the other formats of the `image` crate, TGA by its extension as it has no signature, and SVG,
+//! which `svg` rasterizes with resvg at the UI scale. An SVG entry can give the length of its
+//! longer side in logical pixels as `size`; it is drawn at its own size otherwise.
+//!
+//! The manifest lives in the asset root, found next to the executable unless configured. An icon
+//! or image that cannot be loaded, or a name the manifest lacks, is drawn as a magenta
+//! placeholder and logged rather than stopping the demo; a broken manifest or font is an error.
+//!
+//! ```text
+//! [icons]
+//! rocket = "icon/rocket.png"
+//! logo = { file = "icon/logo.svg", size = 64 }
+//!
+//! [images]
+//! image1 = "images/image1.png"
+//!
+//! [fonts]
+//! Roboto = "fonts/Roboto-Regular.ttf"
+//! ```
+
+use gfx;
+use image;
+use nuklear::nuklear_sys::{nk_rect, nk_subimage_id};
+use nuklear::Image;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use drawer::{Drawer, Texture};
+use icon_atlas;
+use skin_file::{self, Value};
+use svg;
+
+/// The manifest's file name in the asset root.
+pub const MANIFEST: &str = "assets.toml";
+
+/// Edge of the square placeholder texture.
+const PLACEHOLDER_SIZE: u32 = 16;
+
+#[derive(Debug)]
+pub enum AssetError {
+    NotFound { path: PathBuf },
+    Io { path: PathBuf, error: io::Error },
+    Decode { path: PathBuf, error: image::ImageError },
+    /// The file is not in an image format the loader reads.
+    Unsupported { path: PathBuf },
+    /// An SVG file is malformed or too large to rasterize.
+    Svg { path: PathBuf, message: String },
+    /// The manifest cannot be parsed or names something it cannot have.
+    Manifest { path: PathBuf, message: String },
+    /// The manifest has no font of that name.
+    NoFont { name: String },
+    /// A skin file cannot be parsed or sets something the style does not have.
+    Skin { path: PathBuf, message: String },
+    /// The fonts cannot be baked, e.g. as one is no TrueType font.
+    Fonts { message: String },
+    /// The input recording cannot be created.
+    Record { path: PathBuf, error: io::Error },
+}
+
+impl AssetError {
+    pub fn io(path: &Path, error: io::Error) -> AssetError {
+        if error.kind() == io::ErrorKind::NotFound {
+            AssetError::NotFound { path: path.to_path_buf() }
+        } else {
+            AssetError::Io { path: path.to_path_buf(), error }
+        }
+    }
+
+    fn manifest(path: &Path, message: String) -> AssetError {
+        AssetError::Manifest { path: path.to_path_buf(), message }
+    }
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssetError::NotFound { ref path } => write!(f, "{} does not exist", path.display()),
+            AssetError::Io { ref path, ref error } => write!(f, "cannot read {}: {}", path.display(), error),
+            AssetError::Decode { ref path, ref error } => write!(f, "cannot decode {}: {}", path.display(), error),
+            AssetError::Unsupported { ref path } => write!(f, "{} is not in a supported image format", path.display()),
+            AssetError::Svg { ref path, ref message } => write!(f, "cannot rasterize {}: {}", path.display(), message),
+            AssetError::Manifest { ref path, ref message } => write!(f, "{}: {}", path.display(), message),
+            AssetError::NoFont { ref name } => write!(f, "no font named {} in the asset manifest", name),
+            AssetError::Skin { ref path, ref message } => write!(f, "skin {}: {}", path.display(), message),
+            AssetError::Fonts { ref message } => write!(f, "cannot bake the fonts: {}", message),
+            AssetError::Record { ref path, ref error } => write!(f, "cannot create {}: {}", path.display(), error),
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            AssetError::Io { ref error, .. } | AssetError::Record { ref error, .. } => Some(error),
+            AssetError::Decode { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// The asset root: `configured` if given, otherwise the first `res` directory holding a manifest
+/// next to the executable or above it, so installed copies and `target/<profile>` builds alike
+/// find their assets from any working directory. Falls back to `res` in the working directory.
+pub fn root(configured: Option<&Path>) -> PathBuf {
+    if let Some(dir) = configured {
+        return dir.to_path_buf();
+    }
+    let exe = env::current_exe().ok();
+    exe.iter().flat_map(|exe| exe.ancestors().skip(1)).map(|dir| dir.join("res")).find(|dir| dir.join(MANIFEST).is_file()).unwrap_or_else(|| PathBuf::from("res"))
+}
+
+pub struct Asset {
+    pub name: String,
+    pub path: PathBuf,
+    /// The longer side of an SVG image in logical pixels, if the manifest sets it.
+    pub size: Option<u32>,
+}
+
+pub struct Manifest {
+    pub icons: Vec<Asset>,
+    pub images: Vec<Asset>,
+    pub fonts: Vec<Asset>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Manifest, AssetError> {
+        let mut text = String::new();
+        File::open(path).and_then(|mut f| f.read_to_string(&mut text)).map_err(|e| AssetError::io(path, e))?;
+        let entries = skin_file::parse(&text).map_err(|e| AssetError::manifest(path, e))?;
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut manifest = Manifest { icons: Vec::new(), images: Vec::new(), fonts: Vec::new() };
+        for entry in entries {
+            let (file, size) = match entry.value {
+                Value::Str(ref file) => (file, None),
+                Value::Table(ref fields) if entry.section != "fonts" => sized_file(fields).map_err(|e| AssetError::manifest(path, format!("line {}: {}: {}", entry.line, entry.key, e)))?,
+                ref other => return Err(AssetError::manifest(path, format!("line {}: {}: expected a file name, found {}", entry.line, entry.key, other))),
+            };
+            match entry.section.as_str() {
+                "icons" | "images" | "fonts" => (),
+                "" => return Err(AssetError::manifest(path, format!("line {}: '{}' is outside of a section", entry.line, entry.key))),
+                other => return Err(AssetError::manifest(path, format!("line {}: unknown section [{}]", entry.line, other))),
+            }
+            // Icons and images share one registry.
+            if entry.section != "fonts" && manifest.icons.iter().chain(&manifest.images).any(|a| a.name == entry.key) {
+                return Err(AssetError::manifest(path, format!("line {}: there already is an icon or image named {}", entry.line, entry.key)));
+            }
+            let list = match entry.section.as_str() {
+                "icons" => &mut manifest.icons,
+                "images" => &mut manifest.images,
+                _ => &mut manifest.fonts,
+            };
+            list.push(Asset { name: entry.key.clone(), path: dir.join(file), size });
+        }
+        Ok(manifest)
+    }
+
+    /// Reads every font file, by name.
+    pub fn read_fonts(&self) -> Result<FontFiles, AssetError> {
+        let files = self.fonts.iter().map(|font| fs::read(&font.path).map(|data| (font.name.clone(), data)).map_err(|e| AssetError::io(&font.path, e)));
+        Ok(FontFiles { files: files.collect::<Result<_, _>>()? })
+    }
+}
+
+/// The file and size of a `{ file = "...", size = N }` entry.
+fn sized_file(fields: &[(String, Value)]) -> Result<(&String, Option<u32>), String> {
+    let (mut file, mut size) = (None, None);
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("file", Value::Str(f)) => file = Some(f),
+            ("size", &Value::Number(n)) if n >= 1.0 && n.fract() == 0.0 => size = Some(n as u32),
+            ("file", _) | ("size", _) => return Err(format!("bad {} {}", key, value)),
+            _ => return Err(format!("unknown key {}", key)),
+        }
+    }
+    Ok((file.ok_or("the table has no file")?, size))
+}
+
+pub struct FontFiles {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl FontFiles {
+    pub fn get(&self, name: &str) -> Result<&[u8], AssetError> {
+        self.files.iter().find(|&(n, _)| n == name).map(|(_, data)| data.as_slice()).ok_or_else(|| AssetError::NoFont { name: name.to_string() })
+    }
+}
+
+/// The uploaded icons and images of a manifest.
+pub struct Registry {
+    by_name: HashMap<String, Image>,
+    /// The images in manifest order.
+    gallery: Vec<Image>,
+    placeholder: Image,
+    /// Names asked for but missing from the manifest, so each is reported once.
+    unknown: RefCell<Vec<String>>,
+    atlas_stats: Option<icon_atlas::Stats>,
+    /// What the icons and the placeholder are drawn from: the atlas, or a texture each.
+    icon_textures: Vec<Texture>,
+    /// Each image's texture, none for one drawn as the placeholder.
+    image_textures: Vec<Option<Texture>>,
+}
+
+impl Registry {
+    /// Uploads every icon and image of `manifest`, the ones that fail to load as the placeholder,
+    /// rasterizing SVG files at `scale` pixels per logical pixel. The icons and the placeholder
+    /// share one atlas texture unless it would be larger than the GPU supports; images get a
+    /// texture each.
+    pub fn load<F, R: gfx::Resources>(factory: &mut F, drawer: &mut Drawer<R>, manifest: &Manifest, scale: f64) -> Registry
+    where
+        F: gfx::Factory<R>,
+    {
+        let mut registry = Registry {
+            by_name: HashMap::new(),
+            gallery: Vec::new(),
+            placeholder: Image::default(),
+            unknown: RefCell::new(Vec::new()),
+            atlas_stats: None,
+            icon_textures: Vec::new(),
+            image_textures: Vec::new(),
+        };
+        registry.reload_icons(factory, drawer, manifest, scale);
+        for index in 0..manifest.images.len() {
+            registry.reload_image(factory, drawer, manifest, index, scale);
+        }
+        registry
+    }
+
+    /// Loads the icons and the placeholder of `manifest` from disk again and uploads them in a new
+    /// atlas, freeing the previous one. Images drawn as the placeholder move to the new one.
+    pub fn reload_icons<F, R: gfx::Resources>(&mut self, factory: &mut F, drawer: &mut Drawer<R>, manifest: &Manifest, scale: f64)
+    where
+        F: gfx::Factory<R>,
+    {
+        let placeholder_pixels = image::RgbaImage::from_pixel(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, image::Rgba([255, 0, 255, 255]));
+        let icon_pixels: Vec<Option<image::RgbaImage>> = manifest.icons.iter().map(|icon| load_or_warn("icon", icon, scale)).collect();
+
+        let mut pixels = vec![&placeholder_pixels];
+        pixels.extend(icon_pixels.iter().map(|p| p.as_ref().unwrap_or(&placeholder_pixels)));
+        let max_side = factory.get_capabilities().max_texture_size as u32;
+        let (textures, mut icons, atlas_stats) = match icon_atlas::pack(&pixels, max_side) {
+            Some(atlas) => {
+                let texture = upload(factory, drawer, &atlas.image, "icon atlas");
+                let icons: Vec<Image> = atlas.regions.iter().map(|r| sub_image(&texture, r)).collect();
+                (vec![texture], icons, Some(atlas.stats))
+            }
+            None => {
+                eprintln!("warning: the icons do not fit in a {0}x{0} texture, uploading them one by one", max_side);
+                let labels = Some("placeholder".into()).into_iter().chain(manifest.icons.iter().map(|icon| icon.path.to_string_lossy()));
+                let textures: Vec<Texture> = pixels.iter().zip(labels).map(|(p, label)| upload(factory, drawer, p, &label)).collect();
+                let icons = textures.iter().map(Texture::image).collect();
+                (textures, icons, None)
+            }
+        };
+        self.placeholder = icons.remove(0);
+        self.atlas_stats = atlas_stats;
+
+        for (icon, (image, loaded)) in manifest.icons.iter().zip(icons.into_iter().zip(&icon_pixels)) {
+            self.by_name.insert(icon.name.clone(), if loaded.is_some() { image } else { self.placeholder.clone() });
+        }
+        for (index, _) in self.image_textures.iter().enumerate().filter(|(_, texture)| texture.is_none()) {
+            self.gallery[index] = self.placeholder.clone();
+            self.by_name.insert(manifest.images[index].name.clone(), self.placeholder.clone());
+        }
+        // Only now that nothing refers to them.
+        self.icon_textures = textures;
+    }
+
+    /// Loads the image at `index` in `manifest` from disk again and uploads it, freeing the texture
+    /// it had.
+    pub fn reload_image<F, R: gfx::Resources>(&mut self, factory: &mut F, drawer: &mut Drawer<R>, manifest: &Manifest, index: usize, scale: f64)
+    where
+        F: gfx::Factory<R>,
+    {
+        let asset = &manifest.images[index];
+        let texture = load_or_warn("image", asset, scale).map(|p| upload(factory, drawer, &p, &asset.path.to_string_lossy()));
+        let loaded = texture.as_ref().map_or_else(|| self.placeholder.clone(), Texture::image);
+        if index < self.gallery.len() {
+            self.gallery[index] = loaded.clone();
+            self.image_textures[index] = texture;
+        } else {
+            self.gallery.push(loaded.clone());
+            self.image_textures.push(texture);
+        }
+        self.by_name.insert(asset.name.clone(), loaded);
+    }
+
+    /// The icon or image called `name`, or the placeholder if the manifest has none.
+    pub fn get(&self, name: &str) -> Image {
+        if let Some(image) = self.by_name.get(name) {
+            return image.clone();
+        }
+        let mut unknown = self.unknown.borrow_mut();
+        if !unknown.iter().any(|n| n == name) {
+            eprintln!("warning: no icon or image named '{}' in the asset manifest, drawing a placeholder", name);
+            unknown.push(name.to_string());
+        }
+        self.placeholder.clone()
+    }
+
+    pub fn gallery(&self) -> &[Image] {
+        &self.gallery
+    }
+
+    /// The gallery image at `index`, or the placeholder if the gallery is shorter.
+    pub fn gallery_image(&self, index: usize) -> Image {
+        self.gallery.get(index).unwrap_or(&self.placeholder).clone()
+    }
+
+    /// How full the icon atlas is, if the icons fit in one.
+    pub fn atlas_stats(&self) -> Option<icon_atlas::Stats> {
+        self.atlas_stats
+    }
+}
+
+fn load_or_warn(kind: &str, asset: &Asset, scale: f64) -> Option<image::RgbaImage> {
+    load_image(&asset.path, asset.size, scale)
+        .map_err(|e| eprintln!("warning: {} '{}': {}, drawing a placeholder", kind, asset.name, e))
+        .ok()
+}
+
+fn load_image(path: &Path, size: Option<u32>, scale: f64) -> Result<image::RgbaImage, AssetError> {
+    let data = fs::read(path).map_err(|e| AssetError::io(path, e))?;
+    if is_svg(&data) {
+        let svg_error = |message| AssetError::Svg { path: path.to_path_buf(), message };
+        let text = ::std::str::from_utf8(&data).map_err(|e| svg_error(e.to_string()))?;
+        return svg::rasterize(text, size, scale).map_err(svg_error);
+    }
+    let is_tga = path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("tga"));
+    let format = match image::guess_format(&data) {
+        Ok(format) => format,
+        Err(_) if is_tga => image::ImageFormat::TGA,
+        Err(_) => return Err(AssetError::Unsupported { path: path.to_path_buf() }),
+    };
+    let img = image::load_from_memory_with_format(&data, format).map_err(|error| AssetError::Decode { path: path.to_path_buf(), error })?;
+    Ok(img.to_rgba())
+}
+
+/// Whether `data` looks like an XML document rather than a binary image.
+fn is_svg(data: &[u8]) -> bool {
+    let data = if data.starts_with(b"\xef\xbb\xbf") { &data[3..] } else { data };
+    data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'<')
+}
+
+fn upload<F, R: gfx::Resources>(factory: &mut F, drawer: &mut Drawer<R>, img: &image::RgbaImage, label: &str) -> Texture
+where
+    F: gfx::Factory<R>,
+{
+    let (w, h) = img.dimensions();
+    drawer.add_texture(factory, img, w, h, label)
+}
+
+/// The `region` of `texture`.
+fn sub_image(texture: &Texture, region: &icon_atlas::Region) -> Image {
+    let (width, height) = texture.size();
+    let rect = nk_rect { x: region.x as f32, y: region.y as f32, w: region.w as f32, h: region.h as f32 };
+    let mut image = Image::default();
+    *image.as_mut() = unsafe { nk_subimage_id(texture.id(), width as u16, height as u16, rect) };
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads `text` as a manifest kept in the temporary directory.
+    fn load(name: &str, text: &str) -> Result<Manifest, AssetError> {
+        let path = env::temp_dir().join(format!("nuklear-sample-manifest-{}-{}.toml", ::std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        let manifest = Manifest::load(&path);
+        fs::remove_file(&path).unwrap();
+        manifest
+    }
+
+    #[test]
+    fn bundled_manifest_loads() {
+        let manifest = Manifest::load(&Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/res")).join(MANIFEST)).unwrap();
+        assert!(!manifest.icons.is_empty() && !manifest.images.is_empty() && !manifest.fonts.is_empty());
+        for asset in manifest.icons.iter().chain(&manifest.images).chain(&manifest.fonts) {
+            assert!(asset.path.is_file(), "{}", asset.path.display());
+        }
+        manifest.read_fonts().unwrap();
+    }
+
+    #[test]
+    fn sized_entries() {
+        let manifest = load("sized", "[icons]\na = \"a.png\"\n[images]\nb = { file = \"b.svg\", size = 48 }\n").unwrap();
+        assert_eq!(manifest.icons[0].size, None);
+        assert_eq!(manifest.images[0].name, "b");
+        assert_eq!(manifest.images[0].size, Some(48));
+        assert_eq!(manifest.images[0].path, env::temp_dir().join("b.svg"));
+    }
+
+    #[test]
+    fn malformed_manifests_are_errors() {
+        let manifests = [
+            "a = \"a.png\"",
+            "[sounds]\na = \"a.wav\"",
+            "[icons]\na = 1",
+            "[icons]\na = \"a.png\"\n[images]\na = \"b.png\"",
+            "[images]\nb = { file = \"b.svg\", size = 0.5 }",
+            "[images]\nb = { size = 48 }",
+            "[images]\nb = { file = \"b.svg\", colour = 1 }",
+            "[fonts]\nf = { file = \"f.ttf\" }",
+            "[icons\n",
+        ];
+        for (i, text) in manifests.iter().enumerate() {
+            match load(&i.to_string(), text) {
+                Err(AssetError::Manifest { .. }) => (),
+                _ => panic!("accepted {:?}", text),
+            }
+        }
+        assert!(Manifest::load(Path::new("no/such/assets.toml")).is_err());
+    }
+}
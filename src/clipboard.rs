@@ -0,0 +1,148 @@
+//! Clipboard support for Nuklear text edits.
+//!
+//! Nuklear asks for copy and paste through two C callbacks on its context. `install` points them
+//! at a `ClipboardProvider`, either the desktop clipboard (`SystemClipboard`) or a process-local
+//! buffer (`MemoryClipboard`) for headless runs and replayed sessions.
+
+use copypasta;
+use glutin;
+use nuklear::nuklear_sys::{nk_handle, nk_rune, nk_text_edit, nk_utf_decode, NK_UTF_SIZE};
+use nuklear::{Context, TextEdit};
+
+use std::cell::RefCell;
+use std::os::raw::{c_char, c_int, c_void};
+use std::rc::Rc;
+use std::slice;
+
+pub trait ClipboardProvider {
+    /// The current clipboard text, `None` when it is empty or holds something else.
+    fn get(&mut self) -> Option<String>;
+    fn set(&mut self, text: &str);
+}
+
+/// A clipboard that lives inside the process. Clones share their contents, so a caller can keep
+/// one to inspect what the UI copied.
+#[derive(Clone, Default)]
+pub struct MemoryClipboard {
+    contents: Rc<RefCell<Option<String>>>,
+}
+
+impl MemoryClipboard {
+    pub fn contents(&self) -> Option<String> {
+        self.contents.borrow().clone()
+    }
+}
+
+impl ClipboardProvider for MemoryClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.contents()
+    }
+
+    fn set(&mut self, text: &str) {
+        *self.contents.borrow_mut() = Some(text.to_owned());
+    }
+}
+
+/// The desktop clipboard, through Wayland when the window runs on it and X11 otherwise.
+pub struct SystemClipboard {
+    inner: Box<dyn ClipboardProvider>,
+}
+
+impl SystemClipboard {
+    pub fn for_window(window: &glutin::Window) -> Result<SystemClipboard, String> {
+        use glutin::os::unix::WindowExt;
+
+        let inner: Box<dyn ClipboardProvider> = match window.get_wayland_display() {
+            // The display outlives the clipboard: both belong to the window's event loop.
+            Some(display) => Box::new(Desktop(unsafe { copypasta::wayland_clipboard::create_clipboards_from_external(display) }.1)),
+            None => Box::new(Desktop(copypasta::ClipboardContext::new().map_err(|e| format!("cannot open the X11 clipboard: {}", e))?)),
+        };
+        Ok(SystemClipboard { inner })
+    }
+}
+
+impl ClipboardProvider for SystemClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.inner.get()
+    }
+
+    fn set(&mut self, text: &str) {
+        self.inner.set(text)
+    }
+}
+
+/// A `copypasta` clipboard. Its errors mean there is no text to paste, or the copy is lost.
+struct Desktop<C>(C);
+
+impl<C: copypasta::ClipboardProvider> ClipboardProvider for Desktop<C> {
+    fn get(&mut self) -> Option<String> {
+        self.0.get_contents().ok()
+    }
+
+    fn set(&mut self, text: &str) {
+        if let Err(e) = self.0.set_contents(text.to_owned()) {
+            eprintln!("cannot set clipboard: {}", e);
+        }
+    }
+}
+
+/// Keeps the provider a context's clipboard callbacks point at. Must outlive every frame of that
+/// context.
+pub struct Clipboard {
+    _provider: Box<Box<dyn ClipboardProvider>>,
+}
+
+/// Routes copy, cut and paste of `ctx`'s text edits to `provider`.
+pub fn install(ctx: &mut Context, provider: Box<dyn ClipboardProvider>) -> Clipboard {
+    let mut provider = Box::new(provider);
+
+    let clip = ctx.clip_mut();
+    clip.as_mut().userdata = nk_handle { ptr: &mut *provider as *mut Box<dyn ClipboardProvider> as *mut c_void };
+    clip.set_copy(Some(copy));
+    clip.set_paste(Some(paste));
+
+    Clipboard { _provider: provider }
+}
+
+unsafe fn provider<'a>(handle: nk_handle) -> &'a mut Box<dyn ClipboardProvider> {
+    &mut *(handle.ptr as *mut Box<dyn ClipboardProvider>)
+}
+
+/// `len` is the length of the selection in glyphs, not bytes.
+unsafe extern "C" fn copy(handle: nk_handle, text: *const c_char, len: c_int) {
+    if text.is_null() || len <= 0 {
+        return;
+    }
+    let bytes = slice::from_raw_parts(text as *const u8, glyph_bytes(text, len as usize));
+    provider(handle).set(&String::from_utf8_lossy(bytes));
+}
+
+/// The bytes the first `glyphs` glyphs of `text` take, stepping through them as Nuklear does.
+unsafe fn glyph_bytes(text: *const c_char, glyphs: usize) -> usize {
+    let mut rune: nk_rune = 0;
+    (0..glyphs).fold(0, |bytes, _| bytes + nk_utf_decode(text.add(bytes), &mut rune, NK_UTF_SIZE as c_int) as usize)
+}
+
+unsafe extern "C" fn paste(handle: nk_handle, edit: *mut nk_text_edit) {
+    if let Some(text) = provider(handle).get() {
+        let edit = &mut *(edit as *mut TextEdit);
+        edit.paste(&text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_whole_glyphs() {
+        let clipboard = MemoryClipboard::default();
+        let mut provider: Box<dyn ClipboardProvider> = Box::new(clipboard.clone());
+        let handle = nk_handle { ptr: &mut provider as *mut Box<dyn ClipboardProvider> as *mut c_void };
+        let text = "aПривет€ tail";
+        unsafe { copy(handle, text.as_ptr() as *const c_char, 8) };
+        assert_eq!(clipboard.contents().as_deref(), Some("aПривет€"));
+        unsafe { copy(handle, text[1..].as_ptr() as *const c_char, 2) };
+        assert_eq!(clipboard.contents().as_deref(), Some("Пр"));
+    }
+}
@@ -0,0 +1,316 @@
+//! An image gallery popup, combo boxes, checkboxes, a progress bar and a pie menu on right click.
+
+use nuklear::*;
+
+use panel::Panel;
+use session::State;
+use style::StyleScope;
+use Media;
+
+use super::{ui_header, ui_widget, ui_widget_centered};
+
+/// Icons of the pie menu, clockwise from the top.
+const PIE_MENU_ICONS: [&str; 6] = ["home", "phone", "plane", "wifi", "settings", "volume"];
+
+pub struct BasicDemo {
+    image_active: bool,
+    check0: bool,
+    check1: bool,
+    prog: Size,
+    selected_item: usize,
+    selected_image: usize,
+    selected_icon: usize,
+    items: [&'static str; 3],
+    piemenu_active: bool,
+    piemenu_pos: Vec2,
+}
+
+impl Default for BasicDemo {
+    fn default() -> Self {
+        BasicDemo {
+            image_active: false,
+            check0: true,
+            check1: false,
+            prog: 80,
+            selected_item: 0,
+            selected_image: 3,
+            selected_icon: 0,
+            items: ["Item 0", "item 1", "item 2"],
+            piemenu_active: false,
+            piemenu_pos: Vec2::default(),
+        }
+    }
+}
+
+impl BasicDemo {
+    /// Moves the selections into the gallery, which a reloaded manifest can shrink or empty. With
+    /// no images they stay at 0 and draw the placeholder.
+    fn clamp_selections(&mut self, gallery_len: usize) {
+        if self.selected_image >= gallery_len {
+            self.selected_image = 0;
+        }
+        if self.selected_icon >= gallery_len.min(self.items.len()) {
+            self.selected_icon = 0;
+        }
+    }
+}
+
+impl Panel for BasicDemo {
+    fn name(&self) -> &str {
+        "Basic Nuklear Rust!"
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect { x: 320f32, y: 50f32, w: 275f32, h: 610f32 }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, media: &mut Media) {
+        self.clamp_selections(media.icons.gallery().len());
+
+        // ------------------------------------------------
+        //                  POPUP BUTTON
+        // ------------------------------------------------
+
+        ui_header(ctx, media, "Popup & Scrollbar & Images");
+        if ui_widget(ctx, media, 35f32).button_image_text(media.icons.get("directory"), "Images", TextAlignment::Centered as Flags) {
+            self.image_active = !self.image_active;
+        }
+
+        // ------------------------------------------------
+        //                  SELECTED IMAGE
+        // ------------------------------------------------
+        ui_header(ctx, media, "Selected Image");
+        ui_widget_centered(ctx, media, 100f32).image(media.icons.gallery_image(self.selected_image));
+
+        // ------------------------------------------------
+        //                  IMAGE POPUP
+        // ------------------------------------------------
+        if self.image_active && ctx.popup_begin(PopupType::Static, nk_string!("Image Popup"), 0, Rect { x: 265f32, y: 0f32, w: 320f32, h: 220f32 }) {
+            ctx.layout_row_static(82f32, 82, 3);
+            for (i, image) in media.icons.gallery().iter().enumerate() {
+                if ctx.button_image(image.clone()) {
+                    self.selected_image = i;
+                    self.image_active = false;
+                    ctx.popup_close();
+                }
+            }
+            ctx.popup_end();
+        }
+        // ------------------------------------------------
+        //                  COMBOBOX
+        // ------------------------------------------------
+        ui_header(ctx, media, "Combo box");
+        {
+            let mut ctx = ui_widget(ctx, media, 40f32);
+            let widget_width = ctx.widget_width();
+            if ctx.combo_begin_text(self.items[self.selected_item], Vec2 { x: widget_width, y: 200f32 }) {
+                ctx.layout_row_dynamic(35f32, 1);
+                for (i, item) in self.items.iter().enumerate() {
+                    if ctx.combo_item_text(item, TextAlignment::Left as Flags) {
+                        self.selected_item = i;
+                    }
+                }
+                ctx.combo_end();
+            }
+        }
+
+        {
+            let mut ctx = ui_widget(ctx, media, 40f32);
+            let widget_width = ctx.widget_width();
+            if ctx.combo_begin_image_text(self.items[self.selected_icon], media.icons.gallery_image(self.selected_icon), Vec2 { x: widget_width, y: 200f32 }) {
+                ctx.layout_row_dynamic(35f32, 1);
+                // One item per gallery image, up to the item names there are.
+                for (i, (item, image)) in self.items.iter().zip(media.icons.gallery()).enumerate() {
+                    if ctx.combo_item_image_text(image.clone(), item, TextAlignment::Right as Flags) {
+                        self.selected_icon = i;
+                    }
+                }
+                ctx.combo_end();
+            }
+        }
+
+        // ------------------------------------------------
+        //                  CHECKBOX
+        // ------------------------------------------------
+        ui_header(ctx, media, "Checkbox");
+        ui_widget(ctx, media, 30f32).checkbox_text("Flag 1", &mut self.check0);
+        ui_widget(ctx, media, 30f32).checkbox_text("Flag 2", &mut self.check1);
+
+        // ------------------------------------------------
+        //                  PROGRESSBAR
+        // ------------------------------------------------
+        ui_header(ctx, media, "Progressbar");
+        ui_widget(ctx, media, 35f32).progress(&mut self.prog, 100, true);
+
+        // ------------------------------------------------
+        //                  PIEMENU
+        // ------------------------------------------------
+        let bounds = ctx.window_get_bounds();
+        if ctx.input().is_mouse_click_down_in_rect(Button::Right, bounds, true) {
+            self.piemenu_pos = *ctx.input().mouse().pos();
+            self.piemenu_active = true;
+        }
+
+        if self.piemenu_active {
+            let icons: Vec<Image> = PIE_MENU_ICONS.iter().map(|name| media.icons.get(name)).collect();
+            let ret = ui_piemenu(ctx, self.piemenu_pos, 140f32, &icons);
+            if ret == -2 {
+                self.piemenu_active = false;
+            }
+            if ret != -1 {
+                println!("piemenu selected: {}\n", ret);
+                self.piemenu_active = false;
+            }
+        }
+    }
+
+    fn probe(&self, field: &str) -> Option<::std::string::String> {
+        Some(match field {
+            "image_active" => self.image_active.to_string(),
+            "check0" => self.check0.to_string(),
+            "check1" => self.check1.to_string(),
+            "prog" => self.prog.to_string(),
+            "selected_item" => self.selected_item.to_string(),
+            "selected_image" => self.selected_image.to_string(),
+            "selected_icon" => self.selected_icon.to_string(),
+            "piemenu_active" => self.piemenu_active.to_string(),
+            _ => return None,
+        })
+    }
+
+    fn save(&self, state: &mut State) {
+        state.write("check0", self.check0);
+        state.write("check1", self.check1);
+        state.write("prog", self.prog);
+        state.write("selected_item", self.selected_item);
+        state.write("selected_image", self.selected_image);
+        state.write("selected_icon", self.selected_icon);
+    }
+
+    fn restore(&mut self, _ctx: &mut Context, state: &State) {
+        state.read("check0", &mut self.check0);
+        state.read("check1", &mut self.check1);
+        self.prog = state.get("prog").filter(|&p| p <= 100).unwrap_or(self.prog);
+        self.selected_item = state.get("selected_item").filter(|&i| i < self.items.len()).unwrap_or(self.selected_item);
+        // Checked against the gallery when drawn.
+        state.read("selected_image", &mut self.selected_image);
+        self.selected_icon = state.get("selected_icon").filter(|&i| i < self.items.len()).unwrap_or(self.selected_icon);
+    }
+}
+
+// ===============================================================
+//
+//                          CUSTOM WIDGET
+//
+// ===============================================================
+fn ui_piemenu(ctx: &mut Context, pos: Vec2, radius: f32, icons: &[Image]) -> i32 {
+    let mut ret = -1i32;
+    let mut total_space;
+    let mut bounds = Rect::default();
+    let active_item;
+
+    // pie menu popup
+    let mut ctx = StyleScope::new(ctx);
+    ctx.item(|s| &mut s.window.fixed_background, StyleItem::hide()).color(|s| &mut s.window.border_color, color_rgba(0, 0, 0, 0));
+
+    total_space = ctx.window_get_content_region();
+    let open = {
+        // The popup itself goes edge to edge; its content keeps the usual spacing and padding.
+        let mut ctx = StyleScope::new(&mut ctx);
+        ctx.vec2(|s| &mut s.window.spacing, Vec2 { x: 0f32, y: 0f32 }).vec2(|s| &mut s.window.padding, Vec2 { x: 0f32, y: 0f32 });
+        ctx.popup_begin(
+            PopupType::Static,
+            nk_string!("piemenu"),
+            PanelFlags::NoScrollbar as Flags,
+            Rect {
+                x: pos.x - total_space.x - radius,
+                y: pos.y - radius - total_space.y,
+                w: 2f32 * radius,
+                h: 2f32 * radius,
+            },
+        )
+    };
+    if open {
+        total_space = ctx.window_get_content_region();
+        ctx.layout_row_dynamic(total_space.h, 1);
+        ctx.widget(&mut bounds);
+
+        {
+            let mouse = ctx.input().mouse();
+            let out = ctx.window_get_canvas_mut().unwrap();
+
+            // outer circle
+            out.fill_circle(bounds, color_rgb(50, 50, 50));
+            // circle buttons
+            let step = (2f32 * ::std::f32::consts::PI) / (::std::cmp::max(1, icons.len()) as f32);
+            let mut a_min = 0f32;
+            let mut a_max = step;
+
+            let center = Vec2 {
+                x: bounds.x + bounds.w / 2.0f32,
+                y: bounds.y + bounds.h / 2.0f32,
+            };
+            let drag = Vec2 {
+                x: mouse.pos().x - center.x,
+                y: mouse.pos().y - center.y,
+            };
+            let mut angle = drag.y.atan2(drag.x);
+            if angle < -0.0f32 {
+                angle += 2.0f32 * ::std::f32::consts::PI;
+            }
+            active_item = (angle / step) as usize;
+
+            for (i, icon) in icons.iter().enumerate() {
+                let mut content = Rect::default();
+                out.fill_arc(center.x, center.y, bounds.w / 2.0f32, a_min, a_max, if active_item == i { color_rgb(45, 100, 255) } else { color_rgb(60, 60, 60) });
+
+                // separator line
+                let mut rx = bounds.w / 2.0f32;
+                let mut ry = 0f32;
+                let dx = rx * a_min.cos() - ry * a_min.sin();
+                let dy = rx * a_min.sin() + ry * a_min.cos();
+                out.stroke_line(center.x, center.y, center.x + dx, center.y + dy, 1.0f32, color_rgb(50, 50, 50));
+
+                // button content
+                let a = a_min + (a_max - a_min) / 2.0f32;
+                rx = bounds.w / 2.5f32;
+                ry = 0f32;
+                content.w = 30f32;
+                content.h = 30f32;
+                content.x = center.x + ((rx * a.cos() - ry * a.sin()) - content.w / 2.0f32);
+                content.y = center.y + (rx * a.sin() + ry * a.cos() - content.h / 2.0f32);
+                out.draw_image(content, icon, color_rgb(255, 255, 255));
+                a_min = a_max;
+                a_max += step;
+            }
+        }
+        {
+            let out = ctx.window_get_canvas_mut().unwrap();
+
+            // inner circle
+            let inner = Rect {
+                x: bounds.x + bounds.w / 2f32 - bounds.w / 4f32,
+                y: bounds.y + bounds.h / 2f32 - bounds.h / 4f32,
+                w: bounds.w / 2f32,
+                h: bounds.h / 2f32,
+            };
+            out.fill_circle(inner, color_rgb(45, 45, 45));
+
+            // active icon content
+            bounds.w = inner.w / 2.0f32;
+            bounds.h = inner.h / 2.0f32;
+            bounds.x = inner.x + inner.w / 2f32 - bounds.w / 2f32;
+            bounds.y = inner.y + inner.h / 2f32 - bounds.h / 2f32;
+            out.draw_image(bounds, &icons[active_item], color_rgb(255, 255, 255));
+        }
+        ctx.layout_space_end();
+        if !ctx.input().is_mouse_down(Button::Right) {
+            ctx.popup_close();
+            ret = active_item as i32;
+        }
+    } else {
+        ret = -2;
+    }
+    ctx.popup_end();
+    ret
+}
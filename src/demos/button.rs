@@ -0,0 +1,195 @@
+//! Push, repeater, toggle and radio buttons under a menubar with the Music and Theme menus, and a
+//! contextual menu on right click.
+
+use nuklear::*;
+
+use panel::Panel;
+use session::State;
+use style::StyleScope;
+use theme::Theme;
+use Media;
+
+use super::{ui_header, ui_widget};
+
+pub struct ButtonDemo {
+    option: i32,
+    toggle0: bool,
+    toggle1: bool,
+    toggle2: bool,
+    /// Times the repeater has fired.
+    repeats: u32,
+    theme: Theme,
+}
+
+impl Default for ButtonDemo {
+    fn default() -> Self {
+        ButtonDemo {
+            option: 1,
+            toggle0: true,
+            toggle1: false,
+            toggle2: true,
+            repeats: 0,
+            theme: Theme::Default,
+        }
+    }
+}
+
+impl Panel for ButtonDemo {
+    fn name(&self) -> &str {
+        "Button Nuklear Rust!"
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect { x: 50f32, y: 50f32, w: 255f32, h: 610f32 }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, media: &mut Media) {
+        // ------------------------------------------------
+        //                  MENU
+        // ------------------------------------------------
+        ctx.menubar_begin();
+        {
+            // toolbar
+            ctx.layout_row_static(40f32, 40, 5);
+            if ctx.menu_begin_image(nk_string!("Music"), media.icons.get("play"), Vec2 { x: 110f32, y: 120f32 }) {
+                let mut ctx = StyleScope::new(ctx);
+                ctx.font(media.fonts.get("Gecko", 20));
+                // settings
+                ctx.layout_row_dynamic(25f32, 1);
+                ctx.menu_item_image_text(media.icons.get("play"), "Play", TextAlignment::Right as Flags);
+                ctx.menu_item_image_text(media.icons.get("stop"), "Stop", TextAlignment::Right as Flags);
+                ctx.menu_item_image_text(media.icons.get("pause"), "Pause", TextAlignment::Right as Flags);
+                ctx.menu_item_image_text(media.icons.get("next"), "Next", TextAlignment::Right as Flags);
+                ctx.menu_item_image_text(media.icons.get("prev"), "Prev", TextAlignment::Right as Flags);
+                ctx.menu_end();
+            }
+            if ctx.menu_begin_image(nk_string!("Theme"), media.icons.get("tools"), Vec2 { x: 110f32, y: 170f32 }) {
+                ctx.layout_row_dynamic(25f32, 1);
+                for &theme in &Theme::ALL {
+                    let mark = if theme == self.theme { SymbolType::CircleSolid } else { SymbolType::None };
+                    if ctx.menu_item_symbol_text(mark, theme.name(), TextAlignment::Right as Flags) {
+                        self.theme = theme;
+                        theme.apply(ctx);
+                    }
+                }
+                ctx.menu_end();
+            }
+            ctx.button_image(media.icons.get("cloud"));
+            ctx.button_image(media.icons.get("pen"));
+            ctx.button_image(media.icons.get("logo"));
+        }
+        ctx.menubar_end();
+
+        // ------------------------------------------------
+        //                  BUTTON
+        // ------------------------------------------------
+        ui_header(ctx, media, "Push buttons");
+        if ui_widget(ctx, media, 35f32).button_text("Push me") {
+            println!("pushed!");
+        }
+        if ui_widget(ctx, media, 35f32).button_image_text(media.icons.get("rocket"), "Styled", TextAlignment::Centered as Flags) {
+            println!("rocket!");
+        }
+
+        // ------------------------------------------------
+        //                  REPEATER
+        // ------------------------------------------------
+        ui_header(ctx, media, "Repeater");
+        ctx.button_push_behavior(ButtonBehavior::Repeater);
+        if ui_widget(ctx, media, 35f32).button_text("Press me") {
+            self.repeats += 1;
+            println!("pressed!");
+        }
+        ctx.button_pop_behavior();
+
+        // ------------------------------------------------
+        //                  TOGGLE
+        // ------------------------------------------------
+        ui_header(ctx, media, "Toggle buttons");
+        if ui_widget(ctx, media, 35f32).button_image_text(if self.toggle0 { media.icons.get("checked") } else { media.icons.get("unchecked") }, "Toggle", TextAlignment::Left as Flags) {
+            self.toggle0 = !self.toggle0;
+        }
+
+        if ui_widget(ctx, media, 35f32).button_image_text(if self.toggle1 { media.icons.get("checked") } else { media.icons.get("unchecked") }, "Toggle", TextAlignment::Left as Flags) {
+            self.toggle1 = !self.toggle1;
+        }
+
+        if ui_widget(ctx, media, 35f32).button_image_text(if self.toggle2 { media.icons.get("checked") } else { media.icons.get("unchecked") }, "Toggle", TextAlignment::Left as Flags) {
+            self.toggle2 = !self.toggle2;
+        }
+
+        // ------------------------------------------------
+        //                  RADIO
+        // ------------------------------------------------
+        ui_header(ctx, media, "Radio buttons");
+        if ui_widget(ctx, media, 35f32).button_symbol_text(if self.option == 0 { SymbolType::CircleOutline } else { SymbolType::CircleSolid }, "Select 1", TextAlignment::Left as Flags) {
+            self.option = 0;
+        }
+        if ui_widget(ctx, media, 35f32).button_symbol_text(if self.option == 1 { SymbolType::CircleOutline } else { SymbolType::CircleSolid }, "Select 2", TextAlignment::Left as Flags) {
+            self.option = 1;
+        }
+        if ui_widget(ctx, media, 35f32).button_symbol_text(if self.option == 2 { SymbolType::CircleOutline } else { SymbolType::CircleSolid }, "Select 3", TextAlignment::Left as Flags) {
+            self.option = 2;
+        }
+
+        // ------------------------------------------------
+        //                  CONTEXTUAL
+        // ------------------------------------------------
+        {
+            let mut ctx = StyleScope::new(ctx);
+            ctx.font(media.fonts.get("Tusj", 20));
+            let bounds = ctx.window_get_bounds();
+            if ctx.contextual_begin(PanelFlags::NoScrollbar as Flags, Vec2 { x: 150f32, y: 300f32 }, bounds) {
+                ctx.layout_row_dynamic(30f32, 1);
+                if ctx.contextual_item_image_text(media.icons.get("copy"), "Clone", TextAlignment::Right as Flags) {
+                    println!("pressed clone!");
+                }
+                if ctx.contextual_item_image_text(media.icons.get("delete"), "Delete", TextAlignment::Right as Flags) {
+                    println!("pressed delete!");
+                }
+                if ctx.contextual_item_image_text(media.icons.get("export"), "Convert", TextAlignment::Right as Flags) {
+                    println!("pressed convert!");
+                }
+                if ctx.contextual_item_image_text(media.icons.get("edit"), "Edit", TextAlignment::Right as Flags) {
+                    println!("pressed edit!");
+                }
+                ctx.contextual_end();
+            }
+        }
+    }
+
+    fn probe(&self, field: &str) -> Option<::std::string::String> {
+        Some(match field {
+            "option" => self.option.to_string(),
+            "toggle0" => self.toggle0.to_string(),
+            "toggle1" => self.toggle1.to_string(),
+            "toggle2" => self.toggle2.to_string(),
+            "repeats" => self.repeats.to_string(),
+            "theme" => self.theme.name().to_string(),
+            _ => return None,
+        })
+    }
+
+    fn save(&self, state: &mut State) {
+        state.write("option", self.option);
+        state.write("toggle0", self.toggle0);
+        state.write("toggle1", self.toggle1);
+        state.write("toggle2", self.toggle2);
+        state.write("theme", self.theme.name().to_string());
+    }
+
+    fn restore(&mut self, ctx: &mut Context, state: &State) {
+        self.option = state.get("option").filter(|o| (0..3).contains(o)).unwrap_or(self.option);
+        state.read("toggle0", &mut self.toggle0);
+        state.read("toggle1", &mut self.toggle1);
+        state.read("toggle2", &mut self.toggle2);
+        let name: Option<::std::string::String> = state.get("theme");
+        if let Some(&theme) = Theme::ALL.iter().find(|t| name.as_deref() == Some(t.name())) {
+            self.theme = theme;
+            // The default style is in place already, or the skin that replaces it.
+            if theme != Theme::Default {
+                theme.apply(ctx);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Text fields behind Nuklear's edit filters, a checkbox and a combo box in a label grid.
+
+use nuklear::*;
+
+use panel::Panel;
+use session::State;
+use style::StyleScope;
+use Media;
+
+/// Names of the text fields in `text` order.
+const TEXT_KEYS: [&str; 4] = ["float", "hex", "binary", "free"];
+
+/// Nuklear's filters of the text fields before the free one.
+const FILTERS: [PluginFilter; 3] = [NK_FILTER_FLOAT, NK_FILTER_HEX, NK_FILTER_BINARY];
+
+pub struct GridDemo {
+    text: [[u8; 64]; 4],
+    text_len: [i32; 4],
+    items: [&'static str; 4],
+    selected_item: usize,
+    check: bool,
+}
+
+impl Default for GridDemo {
+    fn default() -> Self {
+        GridDemo {
+            text: [[0; 64]; 4],
+            text_len: [0; 4],
+            items: ["Item 0", "item 1", "item 2", "Item 4"],
+            selected_item: 2,
+            check: true,
+        }
+    }
+}
+
+impl GridDemo {
+    /// Sets field `i` to `text` if it could have been typed there: it fits and passes the filter.
+    fn set_text(&mut self, i: usize, text: &str) {
+        // The field keeps one byte for the terminator.
+        if text.len() < self.text[i].len() && accepts(i, text) {
+            self.text[i] = [0; 64];
+            self.text[i][..text.len()].copy_from_slice(text.as_bytes());
+            self.text_len[i] = text.len() as i32;
+        }
+    }
+
+    fn text(&self, i: usize) -> ::std::string::String {
+        ::std::string::String::from_utf8_lossy(&self.text[i][..self.text_len[i] as usize]).into_owned()
+    }
+}
+
+fn free_type(_: &TextEdit, c: char) -> bool {
+    is_free(c)
+}
+
+fn is_free(c: char) -> bool {
+    c > '\u{0030}'
+}
+
+/// Whether the filter of field `i` lets in every character of `text`, as if it were typed.
+fn accepts(i: usize, text: &str) -> bool {
+    match FILTERS.get(i) {
+        Some(&Some(filter)) => {
+            // Nuklear's filters look at the rune only, never at the edit state.
+            let edit: nuklear_sys::nk_text_edit = unsafe { ::std::mem::zeroed() };
+            text.chars().all(|c| unsafe { filter(&edit, c as u32) } != 0)
+        }
+        _ => text.chars().all(is_free),
+    }
+}
+
+impl Panel for GridDemo {
+    fn name(&self) -> &str {
+        "Grid Nuklear Rust!"
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect { x: 600f32, y: 350f32, w: 275f32, h: 250f32 }
+    }
+
+    fn flags(&self) -> Flags {
+        PanelFlags::Border as Flags | PanelFlags::Movable as Flags | PanelFlags::Title as Flags | PanelFlags::Minimizable as Flags | PanelFlags::NoScrollbar as Flags
+    }
+
+    fn draw(&mut self, ctx: &mut Context, media: &mut Media) {
+        let mut ctx = StyleScope::new(ctx);
+        ctx.font(media.fonts.get("Roboto", 20));
+        ctx.layout_row_dynamic(30f32, 2);
+        ctx.text("Free type:", TextAlignment::Right as Flags);
+        ctx.edit_string_custom_filter(EditType::Field as Flags, &mut self.text[3], &mut self.text_len[3], free_type);
+        ctx.text("Floating point:", TextAlignment::Right as Flags);
+        ctx.edit_string(EditType::Field as Flags, &mut self.text[0], &mut self.text_len[0], FILTERS[0]);
+        ctx.text("Hexadecimal:", TextAlignment::Right as Flags);
+        ctx.edit_string(EditType::Field as Flags, &mut self.text[1], &mut self.text_len[1], FILTERS[1]);
+        ctx.text("Binary:", TextAlignment::Right as Flags);
+        ctx.edit_string(EditType::Field as Flags, &mut self.text[2], &mut self.text_len[2], FILTERS[2]);
+        ctx.text("Checkbox:", TextAlignment::Right as Flags);
+        ctx.checkbox_text("Check me", &mut self.check);
+        ctx.text("Combobox:", TextAlignment::Right as Flags);
+
+        let widget_width = ctx.widget_width();
+        if ctx.combo_begin_text(self.items[self.selected_item], Vec2 { x: widget_width, y: 200f32 }) {
+            ctx.layout_row_dynamic(25f32, 1);
+            for i in 0..self.items.len() {
+                if ctx.combo_item_text(self.items[i], TextAlignment::Left as Flags) {
+                    self.selected_item = i;
+                }
+            }
+            ctx.combo_end();
+        }
+    }
+
+    fn probe(&self, field: &str) -> Option<::std::string::String> {
+        if let Some(i) = TEXT_KEYS.iter().position(|&k| k == field) {
+            return Some(self.text(i));
+        }
+
+        Some(match field {
+            "check" => self.check.to_string(),
+            "selected_item" => self.selected_item.to_string(),
+            _ => return None,
+        })
+    }
+
+    fn save(&self, state: &mut State) {
+        for (i, key) in TEXT_KEYS.iter().enumerate() {
+            state.write(key, self.text(i));
+        }
+        state.write("check", self.check);
+        state.write("selected_item", self.selected_item);
+    }
+
+    fn restore(&mut self, _ctx: &mut Context, state: &State) {
+        for (i, key) in TEXT_KEYS.iter().enumerate() {
+            let text: Option<::std::string::String> = state.get(key);
+            if let Some(text) = text {
+                self.set_text(i, &text);
+            }
+        }
+        state.read("check", &mut self.check);
+        self.selected_item = state.get("selected_item").filter(|&i| i < self.items.len()).unwrap_or(self.selected_item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_text_passes_the_filters() {
+        let mut grid = GridDemo::default();
+        for (i, text) in ["-1.5", "c0FFee", "1011", "xyz"].iter().enumerate() {
+            grid.set_text(i, text);
+            assert_eq!(grid.text(i), *text);
+        }
+        for (i, text) in ["1e5", "0x1", "12", "a0"].iter().enumerate() {
+            grid.set_text(i, text);
+            assert_ne!(grid.text(i), *text);
+        }
+        grid.set_text(3, &"x".repeat(64));
+        assert_eq!(grid.text(3), "xyz");
+    }
+}
@@ -0,0 +1,52 @@
+//! The demo panels. `panels` registers them in the order of the Windows menu, so a new demo is a
+//! module here implementing `Panel` and one more `add`.
+
+mod basic;
+mod button;
+mod grid;
+
+use nuklear::*;
+
+use panel::Panels;
+use style::StyleScope;
+use Media;
+
+pub use self::basic::BasicDemo;
+pub use self::button::ButtonDemo;
+pub use self::grid::GridDemo;
+
+/// The demo panels, open and in their default state, under the keys replay scripts use.
+pub fn panels() -> Panels {
+    let mut panels = Panels::default();
+    panels.add("basic", BasicDemo::default());
+    panels.add("button", ButtonDemo::default());
+    panels.add("grid", GridDemo::default());
+    panels
+}
+
+fn ui_header(ctx: &mut Context, media: &Media, title: &str) {
+    let mut ctx = StyleScope::new(ctx);
+    ctx.font(media.fonts.get("Roboto", 20));
+    ctx.layout_row_dynamic(20f32, 1);
+    ctx.text(title, TextAlignment::Left as Flags);
+}
+
+const RATIO_W: [f32; 2] = [0.15f32, 0.85f32];
+/// Lays out a row for one widget and returns the scope to draw it through, in the widget font.
+fn ui_widget<'a>(ctx: &'a mut Context, media: &Media, height: f32) -> StyleScope<'a> {
+    let mut ctx = StyleScope::new(ctx);
+    ctx.font(media.fonts.get("Roboto", 14));
+    ctx.layout_row(LayoutFormat::Dynamic, height, &RATIO_W);
+    // ctx.layout_row_dynamic(height, 1);
+    ctx.spacing(1);
+    ctx
+}
+
+const RATIO_WC: [f32; 3] = [0.15f32, 0.50f32, 0.35f32];
+fn ui_widget_centered<'a>(ctx: &'a mut Context, media: &Media, height: f32) -> StyleScope<'a> {
+    let mut ctx = StyleScope::new(ctx);
+    ctx.font(media.fonts.get("Roboto", 14));
+    ctx.layout_row(LayoutFormat::Dynamic, height, &RATIO_WC);
+    ctx.spacing(1);
+    ctx
+}
@@ -0,0 +1,517 @@
+//! The gfx drawer for Nuklear, after `nuklear-backend-gfx` 0.9, with textures that get freed.
+//!
+//! The backend keeps every texture it is given for as long as it lives, in a private list it only
+//! ever pushes to, so wrapping its `Drawer` cannot free anything, and atlases rebaked and assets
+//! reloaded while the demo runs would pile up on the GPU. Here `add_texture` returns a `Texture`
+//! whose clones share it; once the last one is dropped the drawer frees the texture at the start of
+//! the next `draw` and a later texture takes its slot. Ids carry the generation of their slot, so
+//! an `Image` that outlives its texture draws nothing rather than whatever took its place. Debug
+//! builds report such draws, and textures still held when the drawer is dropped.
+//!
+//! Nuklear 4.0 stretches style images over the whole widget, so `nine_slice` adds a texture that
+//! draws its images in nine pieces instead: corners at their own size, edges stretched along one
+//! axis and the middle along both, which keeps rounded-corner art from being distorted.
+
+use gfx;
+use gfx::format::{U8Norm, Unorm, R8_G8_B8_A8};
+use gfx::handle::{Buffer as GfxBuffer, RenderTargetView, Sampler, ShaderResourceView};
+use gfx::texture::{AaMode, Kind, Mipmap};
+use gfx::traits::FactoryExt;
+use gfx::{Encoder, Factory, Resources};
+use nuklear::nuklear_sys::{nk__begin, nk__next, nk_color, nk_command, nk_command_custom, nk_command_image, nk_command_type_NK_COMMAND_CUSTOM, nk_command_type_NK_COMMAND_IMAGE, nk_draw_list, nk_draw_list_add_image, nk_handle, nk_image, nk_rect};
+use nuklear::{Buffer, Context, ConvertConfig, DrawVertexLayoutAttribute, DrawVertexLayoutElements, DrawVertexLayoutFormat, Handle, Image, Size, Vec2};
+
+use std::cell::RefCell;
+use std::mem;
+use std::os::raw::{c_short, c_ushort, c_void};
+use std::ptr;
+use std::rc::Rc;
+
+use {raw_context, ColorFormat};
+
+// `slice_images` turns image commands into custom ones in place.
+const _: () = assert!(mem::size_of::<nk_command_custom>() <= mem::size_of::<nk_command_image>());
+
+/// Nine-slice margins in texture pixels: left, top, right and bottom.
+pub type Margin = [u16; 4];
+
+gfx_defines! {
+    vertex Vertex {
+        pos: [f32; 2] = "Position",
+        tex: [f32; 2] = "TexCoord",
+        col: [U8Norm; 4] = "Color",
+    }
+
+    constant Locals {
+        proj: [[f32; 4]; 4] = "ProjMtx",
+    }
+
+    pipeline pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+        tex: gfx::TextureSampler<[f32; 4]> = "Texture",
+        output: gfx::BlendTarget<::ColorFormat> = ("Target0", gfx::state::ColorMask::all(), gfx::preset::blend::ALPHA),
+        locals: gfx::ConstantBuffer<Locals> = "Locals",
+        scissors: gfx::Scissor = (),
+    }
+}
+
+/// A texture uploaded to a `Drawer`, shared by its clones and freed after the last one is dropped.
+#[derive(Clone)]
+pub struct Texture(Rc<Shared>);
+
+struct Shared {
+    id: i32,
+    width: u32,
+    height: u32,
+    /// The drawer's list of ids to free.
+    released: Rc<RefCell<Vec<i32>>>,
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        self.released.borrow_mut().push(self.id);
+    }
+}
+
+impl Texture {
+    pub fn id(&self) -> i32 {
+        self.0.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.0.width, self.0.height)
+    }
+
+    pub fn handle(&self) -> Handle {
+        Handle::from_id(self.0.id)
+    }
+
+    /// The whole texture, drawable as long as a clone of `self` is alive.
+    pub fn image(&self) -> Image {
+        Image::with_id(self.0.id)
+    }
+}
+
+/// Slots a texture table can hold; the rest of an id is the generation of its slot.
+const SLOTS: i32 = 1 << 16;
+
+struct Slot<T> {
+    /// Bumped each time the slot is reused.
+    generation: i32,
+    /// Of the latest texture in the slot, kept after it is freed to name it in reports.
+    label: String,
+    view: Option<T>,
+}
+
+/// Textures by id, reusing the slots of freed ones.
+struct Table<T> {
+    slots: Vec<Slot<T>>,
+    /// Indices of the slots whose texture was freed.
+    free: Vec<usize>,
+}
+
+enum Lookup<'a, T> {
+    Live(&'a T),
+    /// With its label, unless a later texture has taken the slot since.
+    Freed(Option<&'a str>),
+    Unknown,
+}
+
+impl<T> Table<T> {
+    fn with_capacity(capacity: usize) -> Table<T> {
+        Table { slots: Vec::with_capacity(capacity), free: Vec::new() }
+    }
+
+    /// Stores `view`, returning its id, which is never 0.
+    fn insert(&mut self, view: T, label: &str) -> i32 {
+        let index = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.generation = (slot.generation + 1) % (i32::MAX / SLOTS);
+                slot.label = label.to_string();
+                slot.view = Some(view);
+                index
+            }
+            None => {
+                assert!(self.slots.len() < SLOTS as usize, "more than {} textures at once", SLOTS);
+                self.slots.push(Slot { generation: 0, label: label.to_string(), view: Some(view) });
+                self.slots.len() - 1
+            }
+        };
+        self.slots[index].generation * SLOTS + index as i32 + 1
+    }
+
+    /// The slot of `id` if it is still the one `id` was given for.
+    fn slot(&self, id: i32) -> Option<(usize, &Slot<T>)> {
+        let index = (id % SLOTS) as usize;
+        let slot = self.slots.get(index.wrapping_sub(1))?;
+        if id > 0 && slot.generation == id / SLOTS {
+            Some((index - 1, slot))
+        } else {
+            None
+        }
+    }
+
+    fn remove(&mut self, id: i32) {
+        if let Some((index, slot)) = self.slot(id) {
+            if slot.view.is_some() {
+                self.slots[index].view = None;
+                self.free.push(index);
+            }
+        }
+    }
+
+    fn get(&self, id: i32) -> Lookup<'_, T> {
+        match self.slot(id) {
+            Some((_, Slot { view: Some(view), .. })) => Lookup::Live(view),
+            Some((_, slot)) => Lookup::Freed(Some(&slot.label)),
+            None if id > 0 && ((id % SLOTS) as usize).wrapping_sub(1) < self.slots.len() => Lookup::Freed(None),
+            None => Lookup::Unknown,
+        }
+    }
+
+    /// The ids and labels of the textures still held.
+    fn live(&self) -> Vec<(i32, &str)> {
+        self.slots.iter().enumerate().filter(|(_, slot)| slot.view.is_some()).map(|(i, slot)| (slot.generation * SLOTS + i as i32 + 1, slot.label.as_str())).collect()
+    }
+}
+
+struct Source<R: Resources> {
+    view: ShaderResourceView<R, [f32; 4]>,
+    /// Set for the textures `nine_slice` adds.
+    margin: Option<Margin>,
+}
+
+/// An image of a nine-slice texture in this frame's commands, drawn by `draw_slice`.
+struct Slice {
+    image: nk_image,
+    margin: Margin,
+    color: nk_color,
+}
+
+pub struct Drawer<R: Resources> {
+    cmd: Buffer,
+    pso: gfx::PipelineState<R, pipe::Meta>,
+    smp: Sampler<R>,
+    textures: Table<Source<R>>,
+    /// Read by `nk_convert` through the commands `slice_images` rewrote.
+    slices: Vec<Slice>,
+    released: Rc<RefCell<Vec<i32>>>,
+    /// Freed textures drawn with, reported once each.
+    stale: Vec<i32>,
+    vbf: GfxBuffer<R, Vertex>,
+    ebf: GfxBuffer<R, u16>,
+    lbf: GfxBuffer<R, Locals>,
+    vsz: usize,
+    esz: usize,
+    vle: DrawVertexLayoutElements,
+
+    pub col: Option<RenderTargetView<R, (R8_G8_B8_A8, Unorm)>>,
+}
+
+impl<R: Resources> Drawer<R> {
+    pub fn new<F>(factory: &mut F, col: RenderTargetView<R, (R8_G8_B8_A8, Unorm)>, texture_count: usize, vbo_size: usize, ebo_size: usize, command_buffer: Buffer) -> Drawer<R>
+    where
+        F: Factory<R>,
+    {
+        use gfx::pso::buffer::Structure;
+
+        let vs: &[u8] = include_bytes!("shaders/vs.glsl");
+        let fs: &[u8] = include_bytes!("shaders/fs.glsl");
+
+        Drawer {
+            cmd: command_buffer,
+            col: Some(col),
+            smp: factory.create_sampler_linear(),
+            pso: factory.create_pipeline_simple(vs, fs, pipe::new()).unwrap(),
+            textures: Table::with_capacity(texture_count + 1),
+            slices: Vec::new(),
+            released: Rc::new(RefCell::new(Vec::new())),
+            stale: Vec::new(),
+            vbf: factory.create_upload_buffer::<Vertex>(vbo_size).unwrap(),
+            ebf: factory.create_upload_buffer::<u16>(ebo_size).unwrap(),
+            vsz: vbo_size,
+            esz: ebo_size,
+            lbf: factory.create_constant_buffer::<Locals>(1),
+            vle: DrawVertexLayoutElements::new(&[
+                (DrawVertexLayoutAttribute::Position, DrawVertexLayoutFormat::Float, Vertex::query("Position").unwrap().offset as Size),
+                (DrawVertexLayoutAttribute::TexCoord, DrawVertexLayoutFormat::Float, Vertex::query("TexCoord").unwrap().offset as Size),
+                (DrawVertexLayoutAttribute::Color, DrawVertexLayoutFormat::R8G8B8A8, Vertex::query("Color").unwrap().offset as Size),
+                (DrawVertexLayoutAttribute::AttributeCount, DrawVertexLayoutFormat::Count, 0),
+            ]),
+        }
+    }
+
+    /// Uploads RGBA `pixels`. `label` names the texture in leak reports.
+    pub fn add_texture<F>(&mut self, factory: &mut F, pixels: &[u8], width: u32, height: u32, label: &str) -> Texture
+    where
+        F: Factory<R>,
+    {
+        let (_, view) = factory.create_texture_immutable_u8::<ColorFormat>(Kind::D2(width as u16, height as u16, AaMode::Single), Mipmap::Provided, &[pixels]).unwrap();
+        self.insert(Source { view, margin: None }, label, width, height)
+    }
+
+    /// The pixels of `texture` as a texture whose images, including sub-images, are drawn
+    /// nine-sliced with `margin`. It holds on to the pixels by itself.
+    pub fn nine_slice(&mut self, texture: &Texture, margin: Margin) -> Texture {
+        let (view, label) = match self.textures.slot(texture.id()) {
+            Some((_, Slot { view: Some(source), label, .. })) => (source.view.clone(), format!("{} sliced {:?}", label, margin)),
+            _ => panic!("texture {} is not one of this drawer's", texture.id()),
+        };
+        let (width, height) = texture.size();
+        self.insert(Source { view, margin: Some(margin) }, &label, width, height)
+    }
+
+    fn insert(&mut self, source: Source<R>, label: &str, width: u32, height: u32) -> Texture {
+        Texture(Rc::new(Shared {
+            id: self.textures.insert(source, label),
+            width,
+            height,
+            released: self.released.clone(),
+        }))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw<F, B: gfx::CommandBuffer<R>>(&mut self, ctx: &mut Context, cfg: &mut ConvertConfig, encoder: &mut Encoder<R, B>, factory: &mut F, width: u32, height: u32, scale: Vec2)
+    where
+        F: Factory<R>,
+    {
+        use gfx::IntoIndexBuffer;
+
+        // Frames already encoded hold on to what they use until flushed.
+        self.free_released();
+
+        if self.col.clone().is_none() {
+            return;
+        }
+
+        let ortho = [
+            [2.0f32 / width as f32, 0.0f32, 0.0f32, 0.0f32],
+            [0.0f32, -2.0f32 / height as f32, 0.0f32, 0.0f32],
+            [0.0f32, 0.0f32, -1.0f32, 0.0f32],
+            [-1.0f32, 1.0f32, 0.0f32, 1.0f32],
+        ];
+
+        self.slice_images(ctx);
+        cfg.set_vertex_layout(&self.vle);
+        cfg.set_vertex_size(::std::mem::size_of::<Vertex>() as Size);
+
+        {
+            let mut rwv = factory.write_mapping(&self.vbf).unwrap();
+            let rvbuf = unsafe { ::std::slice::from_raw_parts_mut(&mut *rwv as *mut [Vertex] as *mut u8, ::std::mem::size_of::<Vertex>() * self.vsz) };
+            let mut vbuf = Buffer::with_fixed(rvbuf);
+
+            let mut rwe = factory.write_mapping(&self.ebf).unwrap();
+            let rebuf = unsafe { ::std::slice::from_raw_parts_mut(&mut *rwe as *mut [u16] as *mut u8, ::std::mem::size_of::<u16>() * self.esz) };
+            let mut ebuf = Buffer::with_fixed(rebuf);
+
+            ctx.convert(&mut self.cmd, &mut vbuf, &mut ebuf, cfg);
+        }
+
+        let mut slice = ::gfx::Slice {
+            start: 0,
+            end: 0,
+            base_vertex: 0,
+            instances: None,
+            buffer: self.ebf.clone().into_index_buffer(factory),
+        };
+
+        encoder.update_constant_buffer(&self.lbf, &Locals { proj: ortho });
+
+        for cmd in ctx.draw_command_iterator(&self.cmd) {
+            if cmd.elem_count() < 1 {
+                continue;
+            }
+
+            slice.end = slice.start + cmd.elem_count();
+
+            let id = cmd.texture().id().unwrap();
+
+            let x = cmd.clip_rect().x * scale.x;
+            let y = cmd.clip_rect().y * scale.y;
+            let w = cmd.clip_rect().w * scale.x;
+            let h = cmd.clip_rect().h * scale.y;
+
+            let sc_rect = gfx::Rect {
+                x: (if x < 0f32 { 0f32 } else { x }) as u16,
+                y: (if y < 0f32 { 0f32 } else { y }) as u16,
+                w: (if x < 0f32 { w + x } else { w }) as u16,
+                h: (if y < 0f32 { h + y } else { h }) as u16,
+            };
+
+            if let Some(res) = Drawer::find_res(&self.textures, &mut self.stale, id) {
+                let data = pipe::Data {
+                    vbuf: self.vbf.clone(),
+                    tex: (res, self.smp.clone()),
+                    output: self.col.clone().unwrap(),
+                    scissors: sc_rect,
+                    locals: self.lbf.clone(),
+                };
+
+                encoder.draw(&slice, &self.pso, &data);
+            }
+
+            slice.start = slice.end;
+        }
+    }
+
+    /// Turns the image commands of nine-slice textures into custom commands that `nk_convert`
+    /// hands to `draw_slice`, pointing them at `self.slices`, which stays put until the next frame.
+    fn slice_images(&mut self, ctx: &mut Context) {
+        let ctx = raw_context(ctx);
+        let mut sliced = Vec::new();
+        self.slices.clear();
+        unsafe {
+            let mut cmd = nk__begin(ctx);
+            while !cmd.is_null() {
+                if (*cmd).type_ == nk_command_type_NK_COMMAND_IMAGE {
+                    let image = &*(cmd as *const nk_command_image);
+                    if let Lookup::Live(Source { margin: Some(margin), .. }) = self.textures.get(image.img.handle.id) {
+                        sliced.push(cmd as *mut nk_command_image);
+                        self.slices.push(Slice { image: image.img, margin: *margin, color: image.col });
+                    }
+                }
+                cmd = nk__next(ctx, cmd);
+            }
+            for (cmd, slice) in sliced.into_iter().zip(&self.slices) {
+                let custom = nk_command_custom {
+                    header: nk_command { type_: nk_command_type_NK_COMMAND_CUSTOM, ..(*cmd).header },
+                    x: (*cmd).x,
+                    y: (*cmd).y,
+                    w: (*cmd).w,
+                    h: (*cmd).h,
+                    callback_data: nk_handle { ptr: slice as *const Slice as *mut c_void },
+                    callback: Some(draw_slice),
+                };
+                ptr::write(cmd as *mut nk_command_custom, custom);
+            }
+        }
+    }
+
+    fn free_released(&mut self) {
+        for id in self.released.borrow_mut().drain(..) {
+            self.textures.remove(id);
+        }
+    }
+
+    /// The texture `id`, if it is live. Takes the fields it needs while the command buffer is borrowed.
+    fn find_res(textures: &Table<Source<R>>, stale: &mut Vec<i32>, id: i32) -> Option<ShaderResourceView<R, [f32; 4]>> {
+        match textures.get(id) {
+            Lookup::Live(source) => Some(source.view.clone()),
+            Lookup::Freed(label) => {
+                if cfg!(debug_assertions) && !stale.contains(&id) {
+                    eprintln!("warning: drawing with texture {} ({}) after it was freed", id, label.unwrap_or("since replaced"));
+                    stale.push(id);
+                }
+                None
+            }
+            Lookup::Unknown => None,
+        }
+    }
+}
+
+unsafe extern "C" fn draw_slice(canvas: *mut c_void, x: c_short, y: c_short, w: c_ushort, h: c_ushort, data: nk_handle) {
+    let slice = &*(data.ptr as *const Slice);
+    let rect = [f32::from(x), f32::from(y), f32::from(w), f32::from(h)];
+    for (region, [x, y, w, h]) in pieces(slice.image.region, slice.margin, rect) {
+        let image = nk_image { region, ..slice.image };
+        nk_draw_list_add_image(canvas as *mut nk_draw_list, image, nk_rect { x, y, w, h }, slice.color);
+    }
+}
+
+/// The nine pieces of `region` cut `margin` in from its edges, as source regions and the parts of
+/// `rect` they cover. Corners keep their size unless `rect` is too small for two of them, when
+/// they shrink to fit; empty pieces are left out.
+fn pieces(region: [u16; 4], margin: Margin, rect: [f32; 4]) -> Vec<([u16; 4], [f32; 4])> {
+    let [sx, sy, sw, sh] = region;
+    let [left, top, right, bottom] = margin;
+    let [x, y, w, h] = rect;
+    // The source span and the destination span of each column or row.
+    let spans = |start: u16, size: u16, low: u16, high: u16, at: f32, room: f32| {
+        let need = f32::from(low) + f32::from(high);
+        let fit = if need > room { room / need } else { 1.0 };
+        let (low_room, high_room) = (f32::from(low) * fit, f32::from(high) * fit);
+        [
+            ((start, low), (at, low_room)),
+            ((start + low, size.saturating_sub(low + high)), (at + low_room, room - low_room - high_room)),
+            ((start + size.saturating_sub(high), high), (at + room - high_room, high_room)),
+        ]
+    };
+    let mut pieces = Vec::with_capacity(9);
+    for &((sy, sh), (y, h)) in &spans(sy, sh, top, bottom, y, h) {
+        for &((sx, sw), (x, w)) in &spans(sx, sw, left, right, x, w) {
+            if sw > 0 && sh > 0 && w > 0. && h > 0. {
+                pieces.push(([sx, sy, sw, sh], [x, y, w, h]));
+            }
+        }
+    }
+    pieces
+}
+
+impl<R: Resources> Drop for Drawer<R> {
+    fn drop(&mut self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        self.free_released();
+        let held: Vec<String> = self.textures.live().iter().map(|&(id, label)| format!("{} ({})", id, label)).collect();
+        if !held.is_empty() {
+            eprintln!("warning: {} textures still held when the drawer was dropped: {}", held.len(), held.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nine_pieces_cover_the_rect() {
+        let all = pieces([10, 20, 30, 16], [4, 5, 6, 3], [100., 50., 200., 40.]);
+        assert_eq!(all.len(), 9);
+        assert_eq!(all[0], ([10, 20, 4, 5], [100., 50., 4., 5.]));
+        assert_eq!(all[4], ([14, 25, 20, 8], [104., 55., 190., 32.]));
+        assert_eq!(all[8], ([34, 33, 6, 3], [294., 87., 6., 3.]));
+        let area: f32 = all.iter().map(|p| p.1[2] * p.1[3]).sum();
+        assert_eq!(area, 200. * 40.);
+
+        // Too narrow for both side columns: they shrink and the middle one goes.
+        let narrow = pieces([0, 0, 30, 30], [4, 4, 4, 4], [0., 0., 4., 30.]);
+        assert_eq!(narrow.len(), 6);
+        assert!(narrow.iter().all(|p| p.1[2] == 2.));
+        // No margin is one piece, the plain stretch.
+        assert_eq!(pieces([1, 2, 3, 4], [0; 4], [0., 0., 9., 9.]), vec![([1, 2, 3, 4], [0., 0., 9., 9.])]);
+    }
+
+    #[test]
+    fn freed_slots_are_reused() {
+        let mut table = Table::with_capacity(1);
+        let kept = table.insert('a', "kept");
+        for _ in 0..100 {
+            let id = table.insert('b', "dropped");
+            table.remove(id);
+        }
+        assert_eq!(table.slots.len(), 2);
+        assert_eq!(table.live(), vec![(kept, "kept")]);
+    }
+
+    #[test]
+    fn stale_ids_find_nothing() {
+        let mut table = Table::with_capacity(1);
+        let old = table.insert('a', "old");
+        table.remove(old);
+        match table.get(old) {
+            Lookup::Freed(Some("old")) => (),
+            _ => panic!("expected a freed texture"),
+        }
+        let new = table.insert('b', "new");
+        assert_ne!(old, new);
+        assert!(matches!(table.get(new), Lookup::Live(&'b')));
+        assert!(matches!(table.get(old), Lookup::Freed(None)));
+        // Removing through a stale id leaves the new texture alone.
+        table.remove(old);
+        assert!(matches!(table.get(new), Lookup::Live(&'b')));
+        assert!(matches!(table.get(0), Lookup::Unknown));
+        assert!(matches!(table.get(new + 1), Lookup::Unknown));
+    }
+}
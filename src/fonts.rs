@@ -0,0 +1,350 @@
+//! Font families baked into one atlas.
+//!
+//! The demo registers its fonts as named families, each with the sizes the UI uses, and `bake`
+//! rasterizes all of them into a single `FontAtlas` texture. A family can list fallback families:
+//! their glyphs are merged into every size of it for the code points its own font leaves out. The
+//! fallbacks' own fallbacks are followed too, depth first, and the first font in that order
+//! covering a code point wins.
+//!
+//! The glyphs a family supplies are given as `Glyphs` sets: scripts, raw code point ranges or the
+//! characters of some sample text. Every glyph of every size takes atlas space, and Nuklear packs
+//! them at most 1024 pixels wide, so CJK sets are best baked at few sizes and with a sample of
+//! the text actually shown where that is known. An atlas taller than the GPU allows is reported
+//! by `bake` rather than uploaded.
+
+use gfx;
+use nuklear::nuklear_sys::nk_font_atlas_add;
+use nuklear::{Allocator, DrawNullTexture, FontAtlas, FontAtlasFormat, FontConfig, UserFont};
+
+use std::cmp;
+
+use drawer::{Drawer, Texture};
+
+/// Nuklear gives up packing glyphs past this atlas height and silently leaves the rest out.
+const MAX_PACKED_HEIGHT: u32 = 1024 * 32;
+
+/// A set of code points to bake.
+#[derive(Debug, Clone, Copy)]
+pub enum Glyphs<'a> {
+    /// Basic Latin and Latin-1.
+    Latin,
+    /// Latin Extended-A and -B.
+    LatinExtended,
+    Greek,
+    /// Cyrillic with its supplement and extensions.
+    Cyrillic,
+    /// CJK punctuation, kana, full-width forms and the unified ideographs, as Nuklear's Chinese
+    /// set.
+    Chinese,
+    /// The same blocks as `Chinese`, which cover Japanese kana and kanji.
+    Japanese,
+    /// CJK punctuation, Hangul compatibility jamo and Hangul syllables.
+    Korean,
+    /// Inclusive code point ranges.
+    Ranges(&'a [(u32, u32)]),
+    /// Every character of the text, for when the strings to show are known.
+    Text(&'a str),
+}
+
+impl<'a> Glyphs<'a> {
+    /// Parses a comma-separated list of set names: `latin`, `latin-extended`, `greek`,
+    /// `cyrillic`, `chinese`, `japanese` and `korean`.
+    pub fn parse_list(list: &str) -> Result<Vec<Glyphs<'a>>, String> {
+        list.split(',')
+            .map(|name| match name.trim() {
+                "latin" => Ok(Glyphs::Latin),
+                "latin-extended" => Ok(Glyphs::LatinExtended),
+                "greek" => Ok(Glyphs::Greek),
+                "cyrillic" => Ok(Glyphs::Cyrillic),
+                "chinese" => Ok(Glyphs::Chinese),
+                "japanese" => Ok(Glyphs::Japanese),
+                "korean" => Ok(Glyphs::Korean),
+                other => Err(format!("unknown glyph set '{}'", other)),
+            })
+            .collect()
+    }
+
+    fn ranges(&self) -> Vec<(u32, u32)> {
+        let cjk: &[(u32, u32)] = &[(0x3000, 0x30FF), (0x31F0, 0x31FF), (0xFF00, 0xFFEF), (0x4E00, 0x9FAF)];
+        match *self {
+            Glyphs::Latin => vec![(0x0020, 0x00FF)],
+            Glyphs::LatinExtended => vec![(0x0100, 0x024F)],
+            Glyphs::Greek => vec![(0x0370, 0x03FF)],
+            Glyphs::Cyrillic => vec![(0x0400, 0x052F), (0x2DE0, 0x2DFF), (0xA640, 0xA69F)],
+            Glyphs::Chinese | Glyphs::Japanese => cjk.to_vec(),
+            Glyphs::Korean => vec![(0x3000, 0x303F), (0x3131, 0x3163), (0xAC00, 0xD7A3)],
+            Glyphs::Ranges(ranges) => ranges.to_vec(),
+            Glyphs::Text(text) => text.chars().filter(|c| !c.is_control()).map(|c| (c as u32, c as u32)).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Family<'a> {
+    pub name: &'a str,
+    /// Only read while baking.
+    pub ttf: &'a [u8],
+    /// The code points the family's own font supplies.
+    pub glyphs: &'a [Glyphs<'a>],
+    pub sizes: &'a [u32],
+    pub fallbacks: &'a [&'a str],
+}
+
+impl<'a> Family<'a> {
+    /// The fallbacks of the family and of its fallbacks in lookup order, each family once.
+    fn fallback_chain<'f>(&self, families: &'f [Family<'a>]) -> Result<Vec<&'f Family<'a>>, String> {
+        let mut chain: Vec<&Family> = Vec::new();
+        let mut pending: Vec<&str> = self.fallbacks.iter().rev().cloned().collect();
+        while let Some(name) = pending.pop() {
+            let fallback = families.iter().find(|f| f.name == name).ok_or_else(|| format!("font {} falls back to unknown family {}", self.name, name))?;
+            if fallback.name != self.name && !chain.iter().any(|f| f.name == name) {
+                chain.push(fallback);
+                pending.extend(fallback.fallbacks.iter().rev());
+            }
+        }
+        Ok(chain)
+    }
+
+    /// The family's code points as sorted, disjoint ranges.
+    fn ranges(&self) -> Vec<(u32, u32)> {
+        normalize(self.glyphs.iter().flat_map(|g| g.ranges()).collect())
+    }
+}
+
+/// The baked fonts, looked up by family name and size.
+pub struct Fonts {
+    atlas: FontAtlas,
+    /// Family and size of each font of the atlas, in the order they were added.
+    index: Vec<(String, u32)>,
+    /// Nuklear reads the glyph ranges of its fonts on every lookup, so they live as long as the
+    /// atlas.
+    _ranges: Vec<Vec<(u32, u32)>>,
+}
+
+impl Fonts {
+    /// The font of `family` at `size` logical pixels. Families are fixed at startup, so asking
+    /// for one that was not registered is a bug and panics.
+    pub fn get(&self, family: &str, size: u32) -> &UserFont {
+        let position = self.index.iter().position(|&(ref f, s)| f == family && s == size).unwrap_or_else(|| panic!("no font {} at size {}", family, size));
+        // The atlas keeps its fonts in the order they were added; merged fonts are not in the list.
+        self.atlas.fonts_iterator().into_iter().nth(position).unwrap().handle()
+    }
+}
+
+/// Bakes `families` into a new atlas texture. Glyphs are rasterized at `scale` times their size,
+/// the hidpi factor times the UI zoom, but measured at their logical size, so text keeps its layout
+/// and stays sharp when the drawer scales the UI to physical pixels.
+///
+/// Fails, uploading nothing, when a font file is not a TrueType font, Nuklear cannot bake it or
+/// the atlas does not fit in a texture the GPU supports.
+pub fn bake<F, R: gfx::Resources>(families: &[Family], factory: &mut F, drawer: &mut Drawer<R>, scale: f64) -> Result<(Fonts, Texture, DrawNullTexture), String>
+where
+    F: gfx::Factory<R>,
+{
+    let max_size = factory.get_capabilities().max_texture_size as u32;
+    build(families, scale, max_size, |pixels, width, height| drawer.add_texture(factory, pixels, width, height, "font atlas"))
+}
+
+fn build<U: FnOnce(&[u8], u32, u32) -> Texture>(families: &[Family], scale: f64, max_size: u32, upload: U) -> Result<(Fonts, Texture, DrawNullTexture), String> {
+    let mut allo = Allocator::new_vec();
+    let mut atlas = FontAtlas::new(&mut allo);
+    let mut index = Vec::new();
+    let mut all_ranges = Vec::new();
+    let mut glyph_counts = Vec::new();
+
+    for family in families {
+        check_ttf(family.ttf).map_err(|e| format!("font {} is not a TrueType font: {}", family.name, e))?;
+    }
+
+    for family in families {
+        let own = family.ranges();
+        if own.is_empty() {
+            return Err(format!("font {} has no glyphs to bake", family.name));
+        }
+
+        // What each fallback adds: its ranges minus everything the fonts before it already cover.
+        let mut covered = own.clone();
+        let mut fallbacks = Vec::new();
+        for fallback in family.fallback_chain(families)? {
+            let ranges = subtract(&fallback.ranges(), &covered);
+            if ranges.is_empty() {
+                continue;
+            }
+            covered = normalize(covered.into_iter().chain(ranges.iter().cloned()).collect());
+            fallbacks.push((fallback.ttf, terminated(ranges)));
+        }
+        if !family.sizes.is_empty() {
+            glyph_counts.push(format!("{} {} glyphs at sizes {:?}", family.name, glyph_count(&covered), family.sizes));
+        }
+
+        let own = terminated(own);
+        for &size in family.sizes {
+            let mut cfg = font_config(family.ttf, &own, size, scale);
+            atlas.add_font_with_config(&cfg).ok_or_else(|| format!("cannot add font {} at size {}", family.name, size))?;
+            index.push((family.name.to_string(), size));
+
+            for &(ttf, ref ranges) in &fallbacks {
+                cfg = font_config(ttf, ranges, size, scale);
+                cfg.set_merge_mode(true);
+                merge_into_last(&mut atlas, &cfg);
+            }
+        }
+        all_ranges.push(own);
+        all_ranges.extend(fallbacks.into_iter().map(|(_, ranges)| ranges));
+    }
+
+    let font_tex = {
+        let (pixels, width, height) = atlas.bake(FontAtlasFormat::Rgba32);
+        if width == 0 || height == 0 || pixels.is_empty() {
+            return Err(format!("Nuklear could not bake the fonts ({})", glyph_counts.join(", ")));
+        }
+        if width > max_size || height > max_size || height >= MAX_PACKED_HEIGHT {
+            return Err(format!(
+                "the font atlas at scale {} needs {}x{} pixels but the GPU takes textures up to {} pixels a side and Nuklear packs up to 1024x{} ({}); bake fewer glyphs or sizes",
+                scale,
+                width,
+                height,
+                max_size,
+                MAX_PACKED_HEIGHT,
+                glyph_counts.join(", ")
+            ));
+        }
+        upload(pixels, width, height)
+    };
+
+    let mut null = DrawNullTexture::default();
+    atlas.end(font_tex.handle(), Some(&mut null));
+
+    // Nuklear scales glyph metrics by the requested height over the baked one.
+    unsafe {
+        let mut font = atlas.as_mut().fonts;
+        while !font.is_null() {
+            (*font).handle.height = (*font).info.height / scale as f32;
+            font = (*font).next;
+        }
+    }
+
+    let fonts = Fonts { atlas, index, _ranges: all_ranges };
+    Ok((fonts, font_tex, null))
+}
+
+/// Checks that the table directory of `ttf` and the tables Nuklear needs lie inside it, since
+/// Nuklear reads them without bounds checks.
+fn check_ttf(ttf: &[u8]) -> Result<(), String> {
+    let u16_at = |at: usize| ttf.get(at..at + 2).map(|b| usize::from(b[0]) << 8 | usize::from(b[1]));
+    let u32_at = |at: usize| ttf.get(at..at + 4).map(|b| (u32::from(b[0]) << 24 | u32::from(b[1]) << 16 | u32::from(b[2]) << 8 | u32::from(b[3])) as usize);
+    let tables = u16_at(4).ok_or("the file is too short")?;
+    let mut found = Vec::new();
+    for record in (0..tables).map(|i| 12 + 16 * i) {
+        let tag = ttf.get(record..record + 4).ok_or("the table directory is cut off")?;
+        let (offset, length) = (u32_at(record + 8).ok_or("the table directory is cut off")?, u32_at(record + 12).ok_or("the table directory is cut off")?);
+        if !offset.checked_add(length).is_some_and(|end| end <= ttf.len()) {
+            return Err(format!("table '{}' is cut off", ::std::string::String::from_utf8_lossy(tag)));
+        }
+        found.push(tag);
+    }
+    match ["cmap", "loca", "head", "glyf", "hhea", "hmtx"].iter().find(|tag| !found.contains(&tag.as_bytes())) {
+        Some(tag) => Err(format!("there is no '{}' table", tag)),
+        None => Ok(()),
+    }
+}
+
+fn font_config<'a>(ttf: &'a [u8], ranges: &'a [(u32, u32)], size: u32, scale: f64) -> FontConfig {
+    let mut cfg = FontConfig::with_size(size as f32 * scale as f32);
+    cfg.set_oversample_h(3);
+    cfg.set_oversample_v(2);
+    cfg.set_glyph_range(ranges);
+    cfg.set_ttf(ttf);
+    cfg.set_ttf_data_owned_by_atlas(false);
+    cfg
+}
+
+/// Merges the glyphs of `cfg` into the font added last.
+fn merge_into_last(atlas: &mut FontAtlas, cfg: &FontConfig) {
+    // Nuklear 4.0 merges into the first font of the atlas whatever was added since, so the last
+    // font is put at the head of the list for the call. The wrapper does not support merging.
+    unsafe {
+        let raw = atlas.as_mut();
+        let first = raw.fonts;
+        let mut last = first;
+        while !(*last).next.is_null() {
+            last = (*last).next;
+        }
+        raw.fonts = last;
+        nk_font_atlas_add(raw, cfg.as_ref());
+        raw.fonts = first;
+    }
+}
+
+/// Sorts inclusive ranges and joins the ones that overlap or touch.
+fn normalize(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    ranges.retain(|&(from, to)| from > 0 && from <= to);
+    ranges.sort();
+    let mut joined: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for (from, to) in ranges {
+        match joined.last_mut() {
+            Some(last) if from <= last.1 + 1 => last.1 = cmp::max(last.1, to),
+            _ => joined.push((from, to)),
+        }
+    }
+    joined
+}
+
+/// Nuklear reads ranges up to a `(0, 0)` pair.
+fn terminated(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    ranges.push((0, 0));
+    ranges
+}
+
+fn glyph_count(ranges: &[(u32, u32)]) -> u32 {
+    ranges.iter().map(|&(from, to)| to - from + 1).sum()
+}
+
+/// The parts of `ranges` outside every range of `taken`. Both are inclusive.
+fn subtract(ranges: &[(u32, u32)], taken: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    let mut left = ranges.to_vec();
+    for &(from, to) in taken {
+        left = left
+            .into_iter()
+            .flat_map(|(a, b)| {
+                let below = if a < from { Some((a, cmp::min(b, from - 1))) } else { None };
+                let above = if b > to { Some((cmp::max(a, to + 1), b)) } else { None };
+                below.into_iter().chain(above)
+            })
+            .collect();
+    }
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::path::Path;
+
+    fn bake_only(ttf: &[u8]) -> Result<(), String> {
+        let family = Family { name: "test", ttf, glyphs: &[Glyphs::Latin], sizes: &[14], fallbacks: &[] };
+        build(&[family], 1.0, 4096, |_, _, _| panic!("uploaded an atlas of a broken font")).map(|_| ())
+    }
+
+    #[test]
+    fn broken_fonts_are_errors() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/fonts");
+        for file in &["Roboto-Regular.ttf", "FFF_Tusj.ttf", "Gecko_PersonalUseOnly.ttf"] {
+            check_ttf(&fs::read(dir.join(file)).unwrap()).unwrap();
+        }
+        let roboto = fs::read(dir.join("Roboto-Regular.ttf")).unwrap();
+        let garbage: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+        for ttf in [&roboto[..roboto.len() / 2], &roboto[..40], &[], &[0; 64], &garbage[..]].iter() {
+            assert!(bake_only(ttf).is_err());
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(normalize(vec![(5, 9), (0, 3), (1, 2), (10, 12), (20, 19)]), vec![(1, 2), (5, 12)]);
+        assert_eq!(subtract(&[(1, 10)], &[(3, 4), (8, 20)]), vec![(1, 2), (5, 7)]);
+        assert_eq!(glyph_count(&[(1, 2), (5, 12)]), 10);
+    }
+}
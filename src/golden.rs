@@ -0,0 +1,153 @@
+//! Golden-image regression checks for the demo windows.
+//!
+//! Every demo is rendered headless from its default state and compared pixel by pixel with a
+//! reference PNG. A pixel matches when no channel differs by more than `tolerance`; a demo passes
+//! when at most `max_mismatch` pixels do not match. Failures leave the actual frame and a diff
+//! image (mismatches in red over the dimmed reference) next to each other in the output directory.
+
+use image::{self, Rgba, RgbaImage};
+
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use assets;
+use headless::Headless;
+use demos;
+use {HEADLESS_HEIGHT, HEADLESS_WIDTH};
+
+/// The checked-in references, in the source tree rather than the working directory.
+pub const REFERENCE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/golden");
+
+pub struct Options {
+    /// Where the demos' assets are.
+    pub asset_root: PathBuf,
+    /// Directory holding the checked-in `<demo>.png` references.
+    pub reference_dir: PathBuf,
+    /// Where actual frames and diff images of failed demos go.
+    pub out_dir: PathBuf,
+    /// Largest per-channel difference still treated as equal.
+    pub tolerance: u8,
+    /// Number of mismatching pixels allowed before a demo fails.
+    pub max_mismatch: usize,
+    /// Overwrite the references with the current output instead of comparing.
+    pub bless: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            asset_root: assets::root(None),
+            reference_dir: PathBuf::from(REFERENCE_DIR),
+            out_dir: PathBuf::from("target/golden"),
+            tolerance: 2,
+            max_mismatch: 0,
+            bless: false,
+        }
+    }
+}
+
+pub struct Comparison {
+    pub mismatched: usize,
+    pub diff: RgbaImage,
+}
+
+/// Compares two frames of the same size and builds the diff image.
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage, tolerance: u8) -> Result<Comparison, String> {
+    if expected.dimensions() != actual.dimensions() {
+        return Err(format!("size mismatch: reference is {:?}, frame is {:?}", expected.dimensions(), actual.dimensions()));
+    }
+
+    let (w, h) = expected.dimensions();
+    let mut diff = RgbaImage::new(w, h);
+    let mut mismatched = 0;
+
+    for (x, y, e) in expected.enumerate_pixels() {
+        let a = actual.get_pixel(x, y);
+        let same = e.data.iter().zip(a.data.iter()).all(|(e, a)| (i16::from(*e) - i16::from(*a)).abs() <= i16::from(tolerance));
+
+        let px = if same {
+            let luma = ((u32::from(e.data[0]) + u32::from(e.data[1]) + u32::from(e.data[2])) / 12) as u8;
+            Rgba([luma, luma, luma, 255])
+        } else {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        };
+        diff.put_pixel(x, y, px);
+    }
+
+    Ok(Comparison { mismatched, diff })
+}
+
+fn check(frame: &RgbaImage, name: &str, opts: &Options) -> Result<(), String> {
+    let reference = opts.reference_dir.join(format!("{}.png", name));
+
+    if opts.bless {
+        create_dir_all(&opts.reference_dir).map_err(|e| format!("cannot create {}: {}", opts.reference_dir.display(), e))?;
+        frame.save(&reference).map_err(|e| format!("cannot write {}: {}", reference.display(), e))?;
+        println!("{}: blessed {}", name, reference.display());
+        return Ok(());
+    }
+
+    let expected = load(&reference)?;
+    let result = compare(&expected, frame, opts.tolerance);
+
+    let failure = match result {
+        Ok(ref cmp) if cmp.mismatched <= opts.max_mismatch => {
+            println!("{}: ok ({} px differ)", name, cmp.mismatched);
+            return Ok(());
+        }
+        Ok(ref cmp) => format!("{} of {} px differ (allowed {})", cmp.mismatched, frame.width() * frame.height(), opts.max_mismatch),
+        Err(ref e) => e.clone(),
+    };
+
+    create_dir_all(&opts.out_dir).map_err(|e| format!("cannot create {}: {}", opts.out_dir.display(), e))?;
+    let actual_path = opts.out_dir.join(format!("{}.actual.png", name));
+    frame.save(&actual_path).map_err(|e| format!("cannot write {}: {}", actual_path.display(), e))?;
+    if let Ok(cmp) = result {
+        let diff_path = opts.out_dir.join(format!("{}.diff.png", name));
+        cmp.diff.save(&diff_path).map_err(|e| format!("cannot write {}: {}", diff_path.display(), e))?;
+    }
+
+    Err(format!("{}: {} (see {})", name, failure, opts.out_dir.display()))
+}
+
+fn load(path: &Path) -> Result<RgbaImage, String> {
+    if !path.exists() {
+        return Err(format!("missing reference {}, run with --bless to create it", path.display()));
+    }
+    image::open(path).map(|img| img.to_rgba()).map_err(|e| format!("cannot read {}: {}", path.display(), e))
+}
+
+/// Renders every demo and checks it against its reference. Returns the failure descriptions.
+pub fn run(opts: &Options) -> Result<Vec<String>, String> {
+    let mut headless = Headless::new(HEADLESS_WIDTH, HEADLESS_HEIGHT, &opts.asset_root)?;
+    let mut failures = Vec::new();
+
+    let mut panels = demos::panels();
+    for key in panels.keys() {
+        let frame = headless.capture(|ctx, media| panels.draw_only(key, ctx, media))?;
+        failures.extend(check(&frame, &format!("{}_demo", key), opts).err());
+    }
+
+    Ok(failures)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_panel_has_a_reference() {
+        let dir = Path::new(REFERENCE_DIR);
+        for key in demos::panels().keys() {
+            let reference = load(&dir.join(format!("{}_demo.png", key))).unwrap();
+            assert_eq!(reference.dimensions(), (u32::from(HEADLESS_WIDTH), u32::from(HEADLESS_HEIGHT)), "{}_demo.png", key);
+        }
+    }
+
+    #[test]
+    fn missing_reference_is_an_error() {
+        assert!(load(&Path::new(REFERENCE_DIR).join("no_such_demo.png")).is_err());
+    }
+}
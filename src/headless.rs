@@ -0,0 +1,239 @@
+//! Offscreen rendering of the demo windows, for machines without a display.
+//!
+//! Instead of `gfx_window_glutin::init` this creates a software OSMesa context, renders into a
+//! texture-backed render target and reads every frame back into a PNG.
+
+use gfx;
+use gfx::format::{ChannelType, Formatted, R8_G8_B8_A8};
+use gfx::handle::{Buffer as GfxBuffer, RenderTargetView, Texture};
+use gfx::memory::{Bind, Typed, Usage};
+use gfx::texture::{AaMode, Kind};
+use gfx::traits::FactoryExt;
+use gfx::{Device, Encoder, Factory};
+use gfx_device_gl;
+use gfx_device_gl::{CommandBuffer, Resources};
+use glutin;
+use glutin::dpi::PhysicalSize;
+use glutin::{ContextTrait, GlProfile, GlRequest};
+use image::{self, RgbaImage};
+use nuklear::{Context, ConvertConfig, Vec2};
+
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::time::Duration;
+
+use assets::{self, FontFiles, Manifest};
+use clipboard::{self, MemoryClipboard};
+use drawer::Drawer;
+use input::InputTranslator;
+use pacing;
+use script::FRAME_MS;
+use demos;
+use {font_families, init_ui, ColorFormat, Media, CLEAR_COLOR};
+
+/// Nuklear needs a frame to lay out popups and combo sizes before the output is stable.
+const SETTLE_FRAMES: usize = 2;
+
+/// A color render target that can be copied back to the CPU.
+pub struct Offscreen<R: gfx::Resources> {
+    pub color: RenderTargetView<R, ColorFormat>,
+    texture: Texture<R, <ColorFormat as Formatted>::Surface>,
+    download: GfxBuffer<R, [u8; 4]>,
+    width: u16,
+    height: u16,
+}
+
+impl<R: gfx::Resources> Offscreen<R> {
+    pub fn new<F: Factory<R>>(factory: &mut F, width: u16, height: u16) -> Result<Offscreen<R>, String> {
+        let kind = Kind::D2(width, height, AaMode::Single);
+        let texture = factory
+            .create_texture::<R8_G8_B8_A8>(kind, 1, Bind::RENDER_TARGET | Bind::TRANSFER_SRC, Usage::Data, Some(ChannelType::Unorm))
+            .map_err(|e| format!("cannot create offscreen texture: {:?}", e))?;
+        let color = factory.view_texture_as_render_target(&texture, 0, None).map_err(|e| format!("cannot create offscreen target: {:?}", e))?;
+        let download = factory
+            .create_download_buffer::<[u8; 4]>(width as usize * height as usize)
+            .map_err(|e| format!("cannot create download buffer: {:?}", e))?;
+
+        Ok(Offscreen { color, texture, download, width, height })
+    }
+
+    /// Copies the current contents of the target into an image, flipping GL's bottom-up rows.
+    pub fn read<F, C, D>(&self, factory: &mut F, encoder: &mut Encoder<R, C>, device: &mut D) -> Result<RgbaImage, String>
+    where
+        F: Factory<R>,
+        C: gfx::CommandBuffer<R>,
+        D: Device<Resources = R, CommandBuffer = C>,
+    {
+        let info = self.texture.get_info().to_raw_image_info(ChannelType::Unorm, 0);
+        encoder.copy_texture_to_buffer_raw(self.texture.raw(), None, info, self.download.raw(), 0).map_err(|e| format!("cannot copy frame: {:?}", e))?;
+        encoder.flush(device);
+
+        let pixels = factory.read_mapping(&self.download).map_err(|e| format!("cannot map frame: {:?}", e))?;
+        let (w, h) = (u32::from(self.width), u32::from(self.height));
+        let mut img = RgbaImage::new(w, h);
+        for (y, row) in pixels.chunks(w as usize).enumerate() {
+            for (x, px) in row.iter().enumerate() {
+                img.put_pixel(x as u32, h - 1 - y as u32, image::Rgba(*px));
+            }
+        }
+        Ok(img)
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (u32::from(self.width), u32::from(self.height))
+    }
+}
+
+/// Creates a software GL context that needs neither a display nor a GPU.
+pub fn create_context(width: u16, height: u16) -> Result<glutin::Context, String> {
+    use glutin::os::unix::OsMesaContextExt;
+
+    let builder = glutin::ContextBuilder::new().with_gl(GlRequest::Specific(glutin::Api::OpenGl, (3, 3))).with_gl_profile(GlProfile::Core);
+    let context = glutin::Context::new_osmesa(builder, PhysicalSize::new(f64::from(width), f64::from(height))).map_err(|e| format!("cannot create OSMesa context: {}", e))?;
+    unsafe { context.make_current() }.map_err(|e| format!("cannot make OSMesa context current: {:?}", e))?;
+    Ok(context)
+}
+
+/// A complete offscreen UI: software GL device, render target and the same Nuklear setup `main` uses.
+pub struct Headless {
+    pub ctx: Context,
+    pub media: Media,
+    /// What the UI copied; `Ctrl+V` pastes from here.
+    pub clipboard: MemoryClipboard,
+    _clipboard_hook: clipboard::Clipboard,
+    drawer: Drawer<Resources>,
+    config: ConvertConfig,
+    input: InputTranslator,
+    /// Kept to rebake the fonts and rasterize the assets again when the zoom changes.
+    manifest: Manifest,
+    font_files: FontFiles,
+    zoom: f64,
+    /// A zoom change to apply after the next frame's input, as the window does.
+    pending_zoom: Option<f64>,
+    target: Offscreen<Resources>,
+    encoder: Encoder<Resources, CommandBuffer>,
+    factory: gfx_device_gl::Factory,
+    device: gfx_device_gl::Device,
+    _context: glutin::Context,
+}
+
+impl Headless {
+    /// Sets up a `width` by `height` UI with the assets of `asset_root`.
+    pub fn new(width: u16, height: u16, asset_root: &Path) -> Result<Headless, String> {
+        let context = create_context(width, height)?;
+        let (device, mut factory) = gfx_device_gl::create(|s| context.get_proc_address(s) as *const _);
+        let encoder: Encoder<_, _> = factory.create_command_buffer().into();
+
+        let target = Offscreen::new(&mut factory, width, height)?;
+        let manifest = Manifest::load(&asset_root.join(assets::MANIFEST)).map_err(|e| e.to_string())?;
+        let font_files = manifest.read_fonts().map_err(|e| e.to_string())?;
+        let families = font_families(&font_files, None).map_err(|e| e.to_string())?;
+        let (drawer, mut ctx, media, config) = init_ui(&mut factory, target.color.clone(), &manifest, &families, 1.0).map_err(|e| e.to_string())?;
+        let memory = MemoryClipboard::default();
+        let hook = clipboard::install(&mut ctx, Box::new(memory.clone()));
+
+        Ok(Headless {
+            ctx,
+            media,
+            clipboard: memory,
+            _clipboard_hook: hook,
+            drawer,
+            config,
+            input: InputTranslator::default(),
+            manifest,
+            font_files,
+            zoom: 1.0,
+            pending_zoom: None,
+            target,
+            encoder,
+            factory,
+            device,
+            _context: context,
+        })
+    }
+
+    /// Zooms the UI to `factor` from the next frame on, after that frame's input like a zoom
+    /// shortcut in the window.
+    pub fn set_zoom(&mut self, factor: f64) {
+        self.pending_zoom = Some(factor);
+    }
+
+    /// Runs one UI frame with the given input and draws it into the offscreen target. Every frame
+    /// counts as `FRAME_MS` long, so runs do not depend on how fast the machine is. A resize
+    /// event resizes the target, in logical pixels as the hidpi factor is 1.
+    pub fn frame<G: FnMut(&mut Context, &mut Media)>(&mut self, events: &[glutin::WindowEvent], mut demo: G) -> Result<(), String> {
+        let mut resized = None;
+
+        self.ctx.input_begin();
+        for event in events {
+            match *event {
+                glutin::WindowEvent::Resized(size) => resized = Some(size),
+                ref event => self.input.handle(&mut self.ctx, event),
+            }
+        }
+        self.ctx.input_end();
+        pacing::set_delta_time(&mut self.ctx, Duration::from_millis(FRAME_MS));
+
+        if let Some(size) = resized {
+            self.target = Offscreen::new(&mut self.factory, size.width.round() as u16, size.height.round() as u16)?;
+            self.drawer.col = Some(self.target.color.clone());
+        }
+        if let Some(factor) = self.pending_zoom.take() {
+            let families = font_families(&self.font_files, None).map_err(|e| e.to_string())?;
+            self.media.rescale(&mut self.factory, &mut self.drawer, &mut self.ctx, &mut self.config, &self.manifest, &families, factor)?;
+            self.input.set_zoom(factor);
+            self.zoom = factor;
+        }
+
+        demo(&mut self.ctx, &mut self.media);
+
+        // Laid out in the target size divided by the zoom, as in the window.
+        let (width, height) = self.target.size();
+        let (width, height) = ((f64::from(width) / self.zoom) as u32, (f64::from(height) / self.zoom) as u32);
+        let scale = Vec2 { x: self.zoom as f32, y: self.zoom as f32 };
+        self.encoder.clear(&self.target.color, CLEAR_COLOR);
+        self.drawer.draw(&mut self.ctx, &mut self.config, &mut self.encoder, &mut self.factory, width, height, scale);
+        self.encoder.flush(&mut self.device);
+        self.device.cleanup();
+
+        self.ctx.clear();
+        Ok(())
+    }
+
+    /// Reads back the last drawn frame.
+    pub fn read(&mut self) -> Result<RgbaImage, String> {
+        self.target.read(&mut self.factory, &mut self.encoder, &mut self.device)
+    }
+
+    /// Draws `demo` until its layout is stable and returns the final frame.
+    pub fn capture<G: FnMut(&mut Context, &mut Media)>(&mut self, mut demo: G) -> Result<RgbaImage, String> {
+        for _ in 0..SETTLE_FRAMES {
+            self.frame(&[], &mut demo)?;
+        }
+        self.read()
+    }
+}
+
+fn save(frame: &RgbaImage, out_dir: &Path, name: &str) -> Result<(), String> {
+    let path = out_dir.join(format!("{}.png", name));
+    frame.save(&path).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+/// Renders each demo window on its own with the assets of `asset_root` and writes
+/// `<out_dir>/<demo>.png`.
+pub fn run(out_dir: &Path, asset_root: &Path, width: u16, height: u16) -> Result<(), String> {
+    let mut headless = Headless::new(width, height, asset_root)?;
+
+    let mut panels = demos::panels();
+
+    create_dir_all(out_dir).map_err(|e| format!("cannot create {}: {}", out_dir.display(), e))?;
+
+    for key in panels.keys() {
+        let frame = headless.capture(|ctx, media| panels.draw_only(key, ctx, media))?;
+        save(&frame, out_dir, &format!("{}_demo", key))?;
+    }
+
+    Ok(())
+}
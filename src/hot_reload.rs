@@ -0,0 +1,129 @@
+//! Reloading assets that change on disk while the demo runs, behind `--watch`.
+//!
+//! A background thread polls the modification time and size of every file under the watched
+//! directories each `INTERVAL`, which needs no platform notification API and is cheap for a
+//! directory of assets. A file is reported once it has stopped changing for a poll, so one still
+//! being written is not picked up half done, and the event loop is woken so on-demand pacing
+//! notices. `classify` then maps the changed files to what has to be reloaded.
+
+use glutin;
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use assets::Manifest;
+
+/// Time between two polls of the watched directories.
+pub const INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct Watcher {
+    changes: Receiver<PathBuf>,
+}
+
+impl Watcher {
+    /// Watches every file under `roots`, waking the event loop of `proxy` when some changed.
+    pub fn spawn(roots: Vec<PathBuf>, proxy: glutin::EventsLoopProxy) -> Watcher {
+        let (sender, changes) = mpsc::channel();
+        let poll = move || {
+            let mut stamps = scan(&roots);
+            let mut pending = BTreeSet::new();
+            loop {
+                thread::sleep(INTERVAL);
+                let now = scan(&roots);
+                let paths: BTreeSet<PathBuf> = now.keys().chain(stamps.keys()).chain(&pending).cloned().collect();
+                let mut settled = Vec::new();
+                for path in paths {
+                    if now.get(&path) != stamps.get(&path) {
+                        pending.insert(path);
+                    } else if pending.remove(&path) {
+                        settled.push(path);
+                    }
+                }
+                stamps = now;
+
+                if settled.is_empty() {
+                    continue;
+                }
+                // The demo has exited once either end is gone.
+                if settled.into_iter().any(|path| sender.send(path).is_err()) || proxy.wakeup().is_err() {
+                    return;
+                }
+            }
+        };
+        thread::Builder::new().name("asset watcher".to_string()).spawn(poll).expect("cannot start the asset watcher");
+        Watcher { changes }
+    }
+
+    /// The files that changed, were created or were removed since the last call.
+    pub fn changed(&self) -> Vec<PathBuf> {
+        self.changes.try_iter().collect()
+    }
+}
+
+/// When each file under `roots` was last modified, and its size.
+fn scan(roots: &[PathBuf]) -> HashMap<PathBuf, (SystemTime, u64)> {
+    let mut stamps = HashMap::new();
+    let mut dirs = roots.to_vec();
+    while let Some(dir) = dirs.pop() {
+        // A directory can vanish between listing and reading it; its files count as removed.
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(Result::ok) {
+            match entry.metadata() {
+                Ok(ref meta) if meta.is_dir() => dirs.push(entry.path()),
+                Ok(meta) => {
+                    stamps.insert(entry.path(), (meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), meta.len()));
+                }
+                Err(_) => (),
+            }
+        }
+    }
+    stamps
+}
+
+/// What a batch of changed files calls for.
+#[derive(Debug, Default)]
+pub struct Reload {
+    /// The manifest itself, after which everything it lists is loaded again.
+    pub manifest: bool,
+    pub icons: bool,
+    /// Indices into the manifest's images.
+    pub images: Vec<usize>,
+    pub fonts: bool,
+    pub skin: bool,
+}
+
+impl Reload {
+    pub fn is_empty(&self) -> bool {
+        !self.manifest && !self.icons && self.images.is_empty() && !self.fonts && !self.skin
+    }
+}
+
+/// Sorts `changed` files into the assets of `manifest`, found at `manifest_path`, and the skin at
+/// `skin`, which is applied again when anything in its directory changes since its atlas lives
+/// next to it. Other files are ignored.
+pub fn classify(changed: &[PathBuf], manifest_path: &Path, manifest: &Manifest, skin: Option<&Path>) -> Reload {
+    // Both sides canonicalized, so `res/./icon/x.png` and `/abs/res/icon/x.png` match. Only the
+    // directory is, as a removed file has no canonical path.
+    let canonical = |path: &Path| match (path.parent().and_then(|dir| fs::canonicalize(dir).ok()), path.file_name()) {
+        (Some(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    };
+    let changed: Vec<PathBuf> = changed.iter().map(|p| canonical(p)).collect();
+    let is_changed = |path: &Path| changed.contains(&canonical(path));
+
+    let skin_dir = skin.map(canonical).and_then(|path| path.parent().map(Path::to_path_buf));
+    Reload {
+        manifest: is_changed(manifest_path),
+        icons: manifest.icons.iter().any(|icon| is_changed(&icon.path)),
+        images: manifest.images.iter().enumerate().filter(|(_, image)| is_changed(&image.path)).map(|(i, _)| i).collect(),
+        fonts: manifest.fonts.iter().any(|font| is_changed(&font.path)),
+        skin: skin_dir.is_some_and(|dir| changed.iter().any(|path| path.parent() == Some(&dir))),
+    }
+}
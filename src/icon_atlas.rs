@@ -0,0 +1,122 @@
+//! Packing of icons into one texture.
+//!
+//! Nuklear batches consecutive draws that use the same texture, so icons sharing an atlas draw in
+//! one call where separate textures need a call each. Icons are placed on shelves, tallest first,
+//! trying atlas widths in steps of `WIDTH_STEP` and keeping the layout with the least area. Each
+//! icon is surrounded by a copy of its edge pixels so linear filtering at its border never
+//! samples a neighbour.
+
+use image::RgbaImage;
+
+use std::cmp::{self, Reverse};
+use std::fmt;
+
+/// Edge pixels repeated around each icon.
+const PADDING: u32 = 1;
+
+/// Atlas widths tried are multiples of it.
+const WIDTH_STEP: u32 = 64;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub width: u32,
+    pub height: u32,
+    pub icons: usize,
+    /// Pixels covered by icons, padding excluded.
+    pub used: u64,
+}
+
+impl Stats {
+    pub fn occupancy(&self) -> f64 {
+        self.used as f64 / (u64::from(self.width) * u64::from(self.height)) as f64
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "icon atlas {}x{}: {} icons, {:.1}% occupied", self.width, self.height, self.icons, self.occupancy() * 100.0)
+    }
+}
+
+pub struct Atlas {
+    pub image: RgbaImage,
+    /// Where each icon went, in the order they were given.
+    pub regions: Vec<Region>,
+    pub stats: Stats,
+}
+
+/// Packs `icons` into an atlas at most `max_side` pixels wide and high, or returns `None` if they
+/// do not fit.
+pub fn pack(icons: &[&RgbaImage], max_side: u32) -> Option<Atlas> {
+    let mut order: Vec<usize> = (0..icons.len()).collect();
+    order.sort_by_key(|&i| (Reverse(icons[i].height()), Reverse(icons[i].width())));
+
+    let padded = |icon: &RgbaImage| (icon.width() + 2 * PADDING, icon.height() + 2 * PADDING);
+    let area: u64 = icons.iter().map(|&icon| padded(icon)).map(|(w, h)| u64::from(w) * u64::from(h)).sum();
+    let widest = icons.iter().map(|&icon| padded(icon).0).max().unwrap_or(1);
+    let narrowest = cmp::max(widest, (area as f64).sqrt() as u32);
+
+    let (width, height, regions) = (narrowest / WIDTH_STEP..=max_side / WIDTH_STEP)
+        .map(|step| cmp::max(step * WIDTH_STEP, narrowest))
+        .map(|width| {
+            let (height, regions) = place(icons, &order, width);
+            (width, height, regions)
+        })
+        .filter(|&(_, height, _)| height <= max_side)
+        .min_by_key(|&(width, height, _)| u64::from(width) * u64::from(height))?;
+
+    let mut image = RgbaImage::new(width, height);
+    for (icon, region) in icons.iter().zip(&regions) {
+        blit_padded(&mut image, icon, region);
+    }
+    let used = icons.iter().map(|icon| u64::from(icon.width()) * u64::from(icon.height())).sum();
+
+    Some(Atlas {
+        image,
+        regions,
+        stats: Stats { width, height, icons: icons.len(), used },
+    })
+}
+
+/// Shelf packing of `icons` visited in `order` into `width` columns. Returns the height used.
+fn place(icons: &[&RgbaImage], order: &[usize], width: u32) -> (u32, Vec<Region>) {
+    let mut regions = vec![Region { x: 0, y: 0, w: 0, h: 0 }; icons.len()];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for &i in order {
+        let (w, h) = (icons[i].width() + 2 * PADDING, icons[i].height() + 2 * PADDING);
+        if x + w > width {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        regions[i] = Region {
+            x: x + PADDING,
+            y: y + PADDING,
+            w: icons[i].width(),
+            h: icons[i].height(),
+        };
+        x += w;
+        shelf_height = cmp::max(shelf_height, h);
+    }
+    (y + shelf_height, regions)
+}
+
+/// Copies `icon` to `region` and repeats its edge pixels into the padding around it.
+fn blit_padded(atlas: &mut RgbaImage, icon: &RgbaImage, region: &Region) {
+    let (w, h) = (icon.width() as i64, icon.height() as i64);
+    let pad = i64::from(PADDING);
+    for dy in -pad..h + pad {
+        for dx in -pad..w + pad {
+            let source = icon.get_pixel(dx.max(0).min(w - 1) as u32, dy.max(0).min(h - 1) as u32);
+            atlas.put_pixel((i64::from(region.x) + dx) as u32, (i64::from(region.y) + dy) as u32, *source);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Translation of glutin window events into Nuklear input.
+//!
+//! The live event loop and script replay both go through `InputTranslator`, so a replayed session
+//! exercises exactly the code a real user does.
+
+use glutin;
+use glutin::dpi::LogicalPosition;
+use nuklear::{Button, Context, Key, Vec2};
+
+pub struct InputTranslator {
+    /// Cursor positions are divided by it to land in the zoomed layout space.
+    zoom: f64,
+    mx: i32,
+    my: i32,
+    lshift: bool,
+    rshift: bool,
+    lctrl: bool,
+    rctrl: bool,
+    lalt: bool,
+    ralt: bool,
+    /// Nuklear keys currently held, by the physical key that pressed them, so a release undoes
+    /// exactly what the press did even if the modifiers changed in between.
+    held: Vec<(glutin::VirtualKeyCode, Key)>,
+}
+
+impl Default for InputTranslator {
+    fn default() -> InputTranslator {
+        InputTranslator {
+            zoom: 1.0,
+            mx: 0,
+            my: 0,
+            lshift: false,
+            rshift: false,
+            lctrl: false,
+            rctrl: false,
+            lalt: false,
+            ralt: false,
+            held: Vec::new(),
+        }
+    }
+}
+
+impl InputTranslator {
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom;
+    }
+
+    /// Feeds one event to `ctx`. Must be called between `input_begin` and `input_end`.
+    pub fn handle(&mut self, ctx: &mut Context, event: &glutin::WindowEvent) {
+        match *event {
+            // Ctrl chords arrive as control characters as well; they are handled as keys. Ctrl with
+            // Alt is AltGr on some platforms, whose characters are text.
+            glutin::WindowEvent::ReceivedCharacter(c) if !c.is_control() && (!self.ctrl() || self.alt()) => {
+                ctx.input_unicode(c);
+            }
+            glutin::WindowEvent::KeyboardInput {
+                input: glutin::KeyboardInput { state, virtual_keycode: Some(k), modifiers, .. },
+                ..
+            } => {
+                let down = state == glutin::ElementState::Pressed;
+                self.track_modifier(k, down);
+
+                if down {
+                    for key in self.map_key(k, modifiers) {
+                        if !self.held.contains(&(k, key)) {
+                            self.held.push((k, key));
+                        }
+                        ctx.input_key(key, true);
+                    }
+                } else {
+                    let (released, held) = self.held.drain(..).partition(|&(hk, _)| hk == k);
+                    self.held = held;
+                    for (_, key) in released {
+                        ctx.input_key(key, false);
+                    }
+                }
+            }
+            // Logical pixels, the space the UI is laid out in regardless of the hidpi factor.
+            glutin::WindowEvent::CursorMoved { position: LogicalPosition { x, y }, .. } => {
+                self.mx = (x / self.zoom) as i32;
+                self.my = (y / self.zoom) as i32;
+                ctx.input_motion(self.mx, self.my);
+            }
+            glutin::WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    glutin::MouseButton::Left => Button::Left,
+                    glutin::MouseButton::Middle => Button::Middle,
+                    glutin::MouseButton::Right => Button::Right,
+                    _ => Button::Max,
+                };
+
+                ctx.input_button(button, self.mx, self.my, state == glutin::ElementState::Pressed)
+            }
+            glutin::WindowEvent::MouseWheel {
+                delta: glutin::MouseScrollDelta::LineDelta(x, y),
+                ..
+            } => {
+                ctx.input_scroll(Vec2 { x: x * 22f32, y: y * 22f32 });
+            }
+            // The releases of keys held when the focus goes go elsewhere.
+            glutin::WindowEvent::Focused(false) => {
+                self.lshift = false;
+                self.rshift = false;
+                self.lctrl = false;
+                self.rctrl = false;
+                self.lalt = false;
+                self.ralt = false;
+                for (_, key) in self.held.drain(..) {
+                    ctx.input_key(key, false);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn ctrl(&self) -> bool {
+        self.lctrl || self.rctrl
+    }
+
+    fn shift(&self) -> bool {
+        self.lshift || self.rshift
+    }
+
+    fn alt(&self) -> bool {
+        self.lalt || self.ralt
+    }
+
+    fn track_modifier(&mut self, k: glutin::VirtualKeyCode, down: bool) {
+        match k {
+            glutin::VirtualKeyCode::LShift => self.lshift = down,
+            glutin::VirtualKeyCode::RShift => self.rshift = down,
+            glutin::VirtualKeyCode::LControl => self.lctrl = down,
+            glutin::VirtualKeyCode::RControl => self.rctrl = down,
+            glutin::VirtualKeyCode::LAlt => self.lalt = down,
+            glutin::VirtualKeyCode::RAlt => self.ralt = down,
+            _ => (),
+        }
+    }
+
+    /// The Nuklear keys a key press stands for, following the upstream SDL/GLFW backends.
+    fn map_key(&self, k: glutin::VirtualKeyCode, modifiers: glutin::ModifiersState) -> Vec<Key> {
+        use glutin::VirtualKeyCode as V;
+
+        let ctrl = modifiers.ctrl || self.ctrl();
+        let shift = modifiers.shift || self.shift();
+
+        match k {
+            V::LShift | V::RShift => vec![Key::Shift],
+            V::LControl | V::RControl => vec![Key::Ctrl],
+            V::Back => vec![Key::Backspace],
+            V::Delete => vec![Key::Del],
+            V::Return | V::NumpadEnter => vec![Key::Enter],
+            V::Tab => vec![Key::Tab],
+            V::Escape => vec![Key::ResetMode],
+            V::Up => vec![Key::Up],
+            V::Down => vec![Key::Down],
+            V::Left if ctrl => vec![Key::TextWordLeft],
+            V::Left => vec![Key::Left],
+            V::Right if ctrl => vec![Key::TextWordRight],
+            V::Right => vec![Key::Right],
+            V::Home if ctrl => vec![Key::TextStart, Key::ScrollStart],
+            V::Home => vec![Key::LineStart, Key::ScrollStart],
+            V::End if ctrl => vec![Key::TextEnd, Key::ScrollEnd],
+            V::End => vec![Key::LineEnd, Key::ScrollEnd],
+            V::PageUp => vec![Key::ScrollUp],
+            V::PageDown => vec![Key::ScrollDown],
+            V::C if ctrl => vec![Key::Copy],
+            V::X if ctrl => vec![Key::Cut],
+            V::V if ctrl => vec![Key::Paste],
+            V::Z if ctrl && shift => vec![Key::TextRedo],
+            V::Z if ctrl => vec![Key::TextUndo],
+            V::Y if ctrl => vec![Key::TextRedo],
+            V::A if ctrl => vec![Key::TextSelectAll],
+            V::B if ctrl => vec![Key::LineStart],
+            V::E if ctrl => vec![Key::LineEnd],
+            _ => vec![],
+        }
+    }
+}
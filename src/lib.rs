@@ -0,0 +1,271 @@
+//! The Nuklear demo windows and the gfx setup they run on, for the demo binary and other tools.
+
+extern crate nuklear;
+
+extern crate copypasta;
+extern crate image;
+extern crate resvg;
+
+#[macro_use]
+extern crate gfx;
+extern crate gfx_device_gl;
+extern crate gfx_window_glutin;
+extern crate glutin;
+
+pub mod app;
+pub mod assets;
+mod clipboard;
+pub mod demos;
+pub mod drawer;
+pub mod fonts;
+pub mod golden;
+pub mod headless;
+mod hot_reload;
+mod icon_atlas;
+mod input;
+pub mod pacing;
+pub mod panel;
+mod record;
+pub mod replay;
+mod script;
+pub mod session;
+pub mod skin;
+mod skin_file;
+pub mod style;
+mod svg;
+pub mod theme;
+mod zoom;
+
+use nuklear::nuklear_sys::nk_context;
+use nuklear::{AntiAliasing, Allocator, Buffer, Context, ConvertConfig};
+
+use assets::{AssetError, FontFiles, Manifest, Registry};
+use drawer::{Drawer, Texture};
+use fonts::{Family, Fonts, Glyphs};
+
+use std::mem;
+use std::path::PathBuf;
+
+pub type ColorFormat = gfx::format::Rgba8;
+pub type DepthFormat = gfx::format::DepthStencil;
+
+const MAX_VERTEX_MEMORY: usize = 512 * 1024;
+const MAX_ELEMENT_MEMORY: usize = 128 * 1024;
+const MAX_COMMANDS_MEMORY: usize = 64 * 1024;
+
+pub const HEADLESS_WIDTH: u16 = 1280;
+pub const HEADLESS_HEIGHT: u16 = 800;
+
+const CLEAR_COLOR: [f32; 4] = [0.1f32, 0.2f32, 0.3f32, 1.0f32];
+
+// `Context` is a struct of one `nk_context` field but not `#[repr(transparent)]`, so Rust does not
+// promise they share a layout. Same size and alignment leave the field nowhere but at offset 0;
+// Cargo.toml pins the wrapper version this holds for.
+const _: () = assert!(mem::size_of::<Context>() == mem::size_of::<nk_context>() && mem::align_of::<Context>() == mem::align_of::<nk_context>());
+
+/// The C context of `ctx`, for the calls and fields the wrapper does not expose.
+pub(crate) fn raw_context(ctx: &mut Context) -> *mut nk_context {
+    let style = ctx.style_mut() as *mut _ as usize;
+    let raw = ctx as *mut Context as *mut nk_context;
+    debug_assert_eq!(style, raw as usize + mem::offset_of!(nk_context, style));
+    raw
+}
+
+/// The fonts, icons and images the UI draws with.
+pub struct Media {
+    pub fonts: Fonts,
+    /// Keeps the font atlas alive while `fonts` point into it.
+    #[allow(dead_code)]
+    font_tex: Texture,
+
+    pub icons: Registry,
+    /// The skin applied to the style, which draws from its atlas.
+    pub skin: Option<skin::Skin>,
+}
+
+
+/// Where the demo window's assets come from, kept to load them again when they change.
+struct Sources<'a> {
+    manifest_path: PathBuf,
+    manifest: Manifest,
+    font_files: FontFiles,
+    fallback: Option<Family<'a>>,
+    skin: Option<PathBuf>,
+}
+
+impl<'a> Sources<'a> {
+    fn families(&self) -> Result<Vec<Family<'_>>, AssetError> {
+        font_families(&self.font_files, self.fallback)
+    }
+}
+
+pub fn arg_value<'a>(args: &'a [::std::string::String], name: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == name).and_then(|i| args.get(i + 1)).map(|s| s.as_str())
+}
+
+/// Builds everything the demos need on top of a gfx factory: the drawer targeting `main_color`,
+/// the font atlas of `families` baked for `scale`, the Nuklear context and the media of
+/// `manifest`. Shared by the windowed and the headless paths so both render exactly the same UI.
+fn init_ui<F, R: gfx::Resources>(factory: &mut F, main_color: gfx::handle::RenderTargetView<R, ColorFormat>, manifest: &Manifest, families: &[Family], scale: f64) -> Result<(Drawer<R>, Context, Media, ConvertConfig), AssetError>
+where
+    F: gfx::Factory<R>,
+{
+    let mut allo = Allocator::new_vec();
+
+    let mut drawer = Drawer::new(factory, main_color, 36, MAX_VERTEX_MEMORY, MAX_ELEMENT_MEMORY, Buffer::with_size(&mut allo, MAX_COMMANDS_MEMORY));
+
+    let (fonts, font_tex, null) = fonts::bake(families, factory, &mut drawer, scale).map_err(|message| AssetError::Fonts { message })?;
+
+    let ctx = Context::new(&mut allo, fonts.get(BASE_FONT.0, BASE_FONT.1));
+
+    let media = Media {
+        fonts,
+        font_tex,
+
+        icons: Registry::load(factory, &mut drawer, manifest, scale),
+        skin: None,
+    };
+
+    let mut config = ConvertConfig::default();
+    config.set_null(null.clone());
+    config.set_circle_segment_count(22);
+    config.set_curve_segment_count(22);
+    config.set_arc_segment_count(22);
+    config.set_global_alpha(1.0f32);
+    config.set_shape_aa(AntiAliasing::On);
+    config.set_line_aa(AntiAliasing::On);
+
+    Ok((drawer, ctx, media, config))
+}
+
+/// Family and size of the font widgets use unless a demo pushes another.
+const BASE_FONT: (&str, u32) = ("Roboto", 22);
+
+/// The fonts the demos use, in logical pixels, with `fallback` behind the base font, and so behind
+/// every family, for text the bundled fonts cannot show. Gecko draws the music menu and Tusj the
+/// contextual menu; they only cover Latin (Gecko some Cyrillic too), so Roboto fills in the rest.
+fn font_families<'a>(files: &'a FontFiles, fallback: Option<Family<'a>>) -> Result<Vec<Family<'a>>, AssetError> {
+    let mut families = vec![
+        Family {
+            name: "Roboto",
+            ttf: files.get("Roboto")?,
+            glyphs: &[Glyphs::Latin, Glyphs::Cyrillic],
+            sizes: &[14, 18, 20, 22],
+            fallbacks: &[],
+        },
+        Family {
+            name: "Gecko",
+            ttf: files.get("Gecko")?,
+            glyphs: &[Glyphs::Latin, Glyphs::Ranges(&[(0x0100, 0x017E), (0x0410, 0x044F)])],
+            sizes: &[20],
+            fallbacks: &["Roboto"],
+        },
+        Family {
+            name: "Tusj",
+            ttf: files.get("Tusj")?,
+            glyphs: &[Glyphs::Ranges(&[(0x0020, 0x007E), (0x00A0, 0x00FF)])],
+            sizes: &[20],
+            fallbacks: &["Roboto"],
+        },
+    ];
+    if let Some(fallback) = fallback {
+        families[0].fallbacks = &["Fallback"];
+        families.push(fallback);
+    }
+    Ok(families)
+}
+
+impl Media {
+    /// Replaces the font atlas with `families` baked at `scale` times their size, for a new hidpi
+    /// factor or zoom, and switches `ctx` and `config` over to it. Call between frames, with no
+    /// style pushed; the previous atlas texture is freed before the next frame is drawn. `families` must
+    /// still provide every font the demos look up. On failure the current fonts stay in use.
+    fn rebake_fonts<F, R: gfx::Resources>(&mut self, factory: &mut F, drawer: &mut Drawer<R>, ctx: &mut Context, config: &mut ConvertConfig, families: &[Family], scale: f64) -> Result<(), ::std::string::String>
+    where
+        F: gfx::Factory<R>,
+    {
+        let (fonts, font_tex, null) = fonts::bake(families, factory, drawer, scale)?;
+
+        // The context points at the old fonts until it is switched, so they are dropped after.
+        ctx.style_set_font(fonts.get(BASE_FONT.0, BASE_FONT.1));
+        config.set_null(null);
+
+        self.fonts = fonts;
+        self.font_tex = font_tex;
+        Ok(())
+    }
+
+    /// Rebakes the fonts and rasterizes the icons and images of `manifest` again at `scale`, for a
+    /// new hidpi factor or zoom, so SVG files stay sharp. The assets are swapped in even if the
+    /// fonts fail to bake, which leaves the current fonts in use.
+    #[allow(clippy::too_many_arguments)]
+    fn rescale<F, R: gfx::Resources>(&mut self, factory: &mut F, drawer: &mut Drawer<R>, ctx: &mut Context, config: &mut ConvertConfig, manifest: &Manifest, families: &[Family], scale: f64) -> Result<(), ::std::string::String>
+    where
+        F: gfx::Factory<R>,
+    {
+        self.icons.reload_icons(factory, drawer, manifest, scale);
+        for index in 0..manifest.images.len() {
+            self.icons.reload_image(factory, drawer, manifest, index, scale);
+        }
+        self.rebake_fonts(factory, drawer, ctx, config, families, scale)
+    }
+
+    /// Loads what `reload` lists from `sources` again at `scale` and swaps it in, between frames
+    /// like `rebake_fonts`. A manifest that no longer loads keeps everything as it was; a broken
+    /// icon or image becomes the placeholder as at startup. Replaced textures are freed.
+    #[allow(clippy::too_many_arguments)]
+    fn reload<F, R: gfx::Resources>(&mut self, factory: &mut F, drawer: &mut Drawer<R>, ctx: &mut Context, config: &mut ConvertConfig, sources: &mut Sources, reload: &hot_reload::Reload, scale: f64)
+    where
+        F: gfx::Factory<R>,
+    {
+        let mut fonts = reload.fonts;
+        if reload.manifest {
+            match Manifest::load(&sources.manifest_path).and_then(|manifest| manifest.read_fonts().map(|files| (manifest, files))) {
+                Ok((manifest, font_files)) => {
+                    eprintln!("reloading {}", sources.manifest_path.display());
+                    sources.manifest = manifest;
+                    sources.font_files = font_files;
+                    self.icons = Registry::load(factory, drawer, &sources.manifest, scale);
+                    fonts = true;
+                }
+                Err(e) => eprintln!("warning: {}, keeping the loaded assets", e),
+            }
+        } else {
+            if reload.icons {
+                eprintln!("reloading the icons");
+                self.icons.reload_icons(factory, drawer, &sources.manifest, scale);
+            }
+            for &index in &reload.images {
+                eprintln!("reloading {}", sources.manifest.images[index].path.display());
+                self.icons.reload_image(factory, drawer, &sources.manifest, index, scale);
+            }
+            if reload.fonts {
+                match sources.manifest.read_fonts() {
+                    Ok(font_files) => sources.font_files = font_files,
+                    Err(e) => {
+                        eprintln!("warning: {}, keeping the current fonts", e);
+                        fonts = false;
+                    }
+                }
+            }
+        }
+
+        if fonts {
+            eprintln!("rebaking the fonts");
+            let rebaked = sources.families().map_err(|e| e.to_string()).and_then(|families| self.rebake_fonts(factory, drawer, ctx, config, &families, scale));
+            if let Err(e) = rebaked {
+                eprintln!("warning: {}, keeping the current fonts", e);
+            }
+        }
+        if let (true, Some(path)) = (reload.skin, sources.skin.as_ref()) {
+            eprintln!("reapplying the skin {}", path.display());
+            match skin::Skin::load(factory, drawer, path) {
+                Ok(skin) => {
+                    skin.apply(ctx);
+                    self.skin = Some(skin);
+                }
+                Err(e) => eprintln!("warning: {}, keeping the current skin", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Frame pacing for the demo window.
+//!
+//! In continuous mode a frame is drawn at a fixed rate whether or not anything happened. In
+//! on-demand mode the loop blocks until the window gets an event and then draws just enough frames
+//! for Nuklear to settle, or keeps going while something animates.
+
+use glutin;
+use nuklear::Context;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use raw_context;
+
+/// Frame rate of the continuous mode unless `--fps` says otherwise.
+pub const DEFAULT_FPS: u32 = 50;
+
+/// Frames drawn after the last input: Nuklear reacts to input in the frame it arrives and only
+/// lays out what depends on that reaction, like popups and combo sizes, in the next one.
+const SETTLE_FRAMES: u32 = 2;
+
+/// Delta of the first frame, and of the first after the on-demand mode waited for input.
+const IDLE_DELTA: Duration = Duration::from_millis(1000 / DEFAULT_FPS as u64);
+
+#[derive(Clone, Copy)]
+pub enum Mode {
+    Continuous { fps: u32 },
+    OnDemand,
+}
+
+pub struct FramePacer {
+    mode: Mode,
+    frame_start: Instant,
+    delta: Duration,
+    pending: u32,
+    animating: bool,
+}
+
+impl FramePacer {
+    pub fn new(mode: Mode) -> FramePacer {
+        FramePacer {
+            mode,
+            frame_start: Instant::now(),
+            delta: IDLE_DELTA,
+            pending: SETTLE_FRAMES,
+            animating: false,
+        }
+    }
+
+    /// Hands the pending events to `handler`. In on-demand mode with nothing left to draw this
+    /// blocks until the window gets an event first.
+    pub fn wait_events<F: FnMut(glutin::Event)>(&mut self, events_loop: &mut glutin::EventsLoop, mut handler: F) {
+        let mut woken = false;
+        let mut idled = false;
+
+        if let Mode::OnDemand = self.mode {
+            if self.pending == 0 && !self.animating {
+                events_loop.run_forever(|event| {
+                    // Raw device events arrive whether or not the window is focused.
+                    let wakes = wakes_ui(&event);
+                    handler(event);
+                    if wakes {
+                        glutin::ControlFlow::Break
+                    } else {
+                        glutin::ControlFlow::Continue
+                    }
+                });
+                woken = true;
+                idled = true;
+            }
+        }
+
+        events_loop.poll_events(|event| {
+            woken |= wakes_ui(&event);
+            handler(event);
+        });
+
+        if woken {
+            self.pending = SETTLE_FRAMES;
+        }
+        self.animating = false;
+
+        // Time spent waiting for input is not frame time; counting it would make repeating buttons
+        // and the like jump ahead.
+        let now = Instant::now();
+        self.delta = if idled { IDLE_DELTA } else { now - self.frame_start };
+        self.frame_start = now;
+    }
+
+    /// Time between the start of the previous frame and the current one, or a nominal frame after
+    /// an idle wait.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Call after a frame has been presented. Keeps drawing while a mouse button is held, since
+    /// sliders, scrollbars and repeating buttons change without new events, and in continuous
+    /// mode sleeps out the rest of the frame.
+    pub fn end_frame(&mut self, ctx: &Context) {
+        self.pending = self.pending.saturating_sub(1);
+        if ctx.input().mouse().buttons().iter().any(|b| b.down) {
+            self.animating = true;
+        }
+
+        if let Mode::Continuous { fps } = self.mode {
+            let frame = Duration::from_secs(1) / fps.max(1);
+            let elapsed = self.frame_start.elapsed();
+            if elapsed < frame {
+                thread::sleep(frame - elapsed);
+            }
+        }
+    }
+}
+
+fn wakes_ui(event: &glutin::Event) -> bool {
+    matches!(*event, glutin::Event::WindowEvent { .. } | glutin::Event::Awakened)
+}
+
+/// Tells Nuklear how long the last frame took; it times things like scrollbar auto-hiding with it.
+pub fn set_delta_time(ctx: &mut Context, delta: Duration) {
+    // The wrapper has a getter only.
+    unsafe {
+        (*raw_context(ctx)).delta_time_seconds = delta.as_secs_f32();
+    }
+}
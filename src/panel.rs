@@ -0,0 +1,258 @@
+//! Windows as pluggable panels, and the Windows menu that opens and closes them.
+//!
+//! A `Panel` is one window: it names itself, says where it opens and lays out its contents, and
+//! `Panels` calls `begin` and `end` around that. `Panels` keeps them in menu order under short
+//! keys, which replay scripts, golden images and the session file refer to them by, and is itself
+//! an `App`, so a registry of panels is a complete tool. Given a session file it restores the
+//! windows and panel state saved there on startup and saves them again on exit.
+
+use nuklear::nuklear_sys::nk_window_flags_NK_WINDOW_MINIMIZED;
+use nuklear::{Context, Flags, PanelFlags, Rect, SymbolType, TextAlignment, Vec2};
+
+use std::path::PathBuf;
+
+use app::App;
+use session::{self, Session, State};
+use style::StyleScope;
+use Media;
+
+/// Font of the panel titles, and of their contents unless a panel pushes another.
+const PANEL_FONT: (&str, u32) = ("Roboto", 18);
+
+/// How much of a restored window stays on screen at least: enough of its title bar to drag it by.
+const GRIP: f32 = 40f32;
+
+/// Window holding the Windows menu, in the corner the panels leave free.
+const MENU_WINDOW: &str = "Menu";
+const MENU_BOUNDS: Rect = Rect { x: 0f32, y: 0f32, w: 110f32, h: 40f32 };
+
+pub trait Panel {
+    /// The window title, also its entry in the Windows menu. Nuklear tells windows apart by their
+    /// title, so it must be unique.
+    fn name(&self) -> &str;
+
+    /// Where the window opens.
+    fn bounds(&self) -> Rect;
+
+    fn flags(&self) -> Flags {
+        PanelFlags::Border as Flags | PanelFlags::Movable as Flags | PanelFlags::Title as Flags | PanelFlags::Minimizable as Flags
+    }
+
+    /// Lays out the window's contents. Not called while the window is minimized.
+    fn draw(&mut self, ctx: &mut Context, media: &mut Media);
+
+    /// The value of the state `field` as replay scripts write it, e.g. `selected_item`.
+    fn probe(&self, _field: &str) -> Option<::std::string::String> {
+        None
+    }
+
+    /// Writes the state worth keeping between runs. `window` is taken by the window layout.
+    fn save(&self, _state: &mut State) {}
+
+    /// Takes back what `save` wrote, before the first frame. Whatever `state` lacks stays as it
+    /// is, and values read back need the same checks as user input.
+    fn restore(&mut self, _ctx: &mut Context, _state: &State) {}
+}
+
+struct Entry {
+    key: &'static str,
+    panel: Box<dyn Panel>,
+    open: bool,
+    /// Where the window is, as of the last frame it was drawn.
+    bounds: Rect,
+    minimized: bool,
+    /// Whether Nuklear has the window. It forgets windows not drawn for a frame, and takes
+    /// `bounds` and `minimized` when it creates them again.
+    live: bool,
+}
+
+/// The panels of a tool, drawn in the order they were added.
+#[derive(Default)]
+pub struct Panels {
+    entries: Vec<Entry>,
+    session: Option<PathBuf>,
+}
+
+impl Panels {
+    /// Adds `panel`, open, under `key`.
+    pub fn add<P: Panel + 'static>(&mut self, key: &'static str, panel: P) {
+        assert!(self.entries.iter().all(|e| e.key != key), "panel key {} registered twice", key);
+        assert!(panel.name() != MENU_WINDOW, "panel {} is named like the menu window", key);
+        let bounds = panel.bounds();
+        self.entries.push(Entry {
+            key,
+            panel: Box::new(panel),
+            open: true,
+            bounds,
+            minimized: false,
+            live: false,
+        });
+    }
+
+    /// Restores the session at `path` in `App::init` and saves it there in `App::shutdown`, unless
+    /// the file there could not be read, e.g. as another version wrote it.
+    pub fn set_session(&mut self, path: PathBuf) {
+        self.session = Some(path);
+    }
+
+    pub fn keys(&self) -> Vec<&'static str> {
+        self.entries.iter().map(|e| e.key).collect()
+    }
+
+    /// Draws the Windows menu and every open panel.
+    pub fn draw(&mut self, ctx: &mut Context, media: &mut Media) {
+        self.draw_menu(ctx, media);
+        for entry in &mut self.entries {
+            if entry.open {
+                entry.show(ctx, media);
+            } else {
+                entry.live = false;
+            }
+        }
+    }
+
+    /// Draws the panel under `key` alone, open or not and without the menu, for rendering it on
+    /// its own. An unknown key draws nothing.
+    pub fn draw_only(&mut self, key: &str, ctx: &mut Context, media: &mut Media) {
+        for entry in &mut self.entries {
+            if entry.key == key {
+                entry.show(ctx, media);
+            } else {
+                entry.live = false;
+            }
+        }
+    }
+
+    /// Looks up a panel's state by a `key.field` path, e.g. `basic.selected_image`. The field
+    /// `open` is whether the panel is open.
+    pub fn probe(&self, path: &str) -> Option<::std::string::String> {
+        let (key, field) = path.split_once('.')?;
+        let entry = self.entries.iter().find(|e| e.key == key)?;
+        match field {
+            "open" => Some(entry.open.to_string()),
+            field => entry.panel.probe(field),
+        }
+    }
+
+    /// Puts the windows and panel state of `session` in place, before the first frame. Windows are
+    /// moved back into a UI of `size` as far as it takes to drag them by their title bar.
+    pub fn restore(&mut self, ctx: &mut Context, session: &Session, size: Vec2) {
+        for entry in &mut self.entries {
+            if let Some(saved) = session.panel(entry.key) {
+                let window = saved.window;
+                entry.bounds = window.bounds.map_or(entry.bounds, |bounds| on_screen(bounds, size));
+                entry.minimized = window.minimized.unwrap_or(entry.minimized);
+                entry.open = window.open.unwrap_or(entry.open);
+                entry.panel.restore(ctx, &saved.state);
+            }
+        }
+    }
+
+    /// The windows and panel state as they are now.
+    pub fn save(&self) -> Session {
+        let mut session = Session::default();
+        for entry in &self.entries {
+            let saved = session.panel_mut(entry.key);
+            saved.window = session::Window {
+                bounds: Some(entry.bounds),
+                minimized: Some(entry.minimized),
+                open: Some(entry.open),
+            };
+            entry.panel.save(&mut saved.state);
+        }
+        session
+    }
+
+    fn draw_menu(&mut self, ctx: &mut Context, media: &Media) {
+        let mut ctx = StyleScope::new(ctx);
+        ctx.font(media.fonts.get(PANEL_FONT.0, PANEL_FONT.1));
+
+        if ctx.begin(::nuklear::String::from(MENU_WINDOW), MENU_BOUNDS, PanelFlags::Border as Flags | PanelFlags::NoScrollbar as Flags) {
+            ctx.menubar_begin();
+            ctx.layout_row_static(25f32, 90, 1);
+            if ctx.menu_begin_text("Windows", TextAlignment::Left as Flags, Vec2 { x: 220f32, y: 200f32 }) {
+                ctx.layout_row_dynamic(25f32, 1);
+                for entry in &mut self.entries {
+                    let mark = if entry.open { SymbolType::RectSolid } else { SymbolType::RectOutline };
+                    if ctx.menu_item_symbol_text(mark, entry.panel.name(), TextAlignment::Right as Flags) {
+                        entry.open = !entry.open;
+                    }
+                }
+                ctx.menu_end();
+            }
+            ctx.menubar_end();
+        }
+        ctx.end();
+    }
+}
+
+impl App for Panels {
+    fn init(&mut self, ctx: &mut Context, _media: &mut Media, size: Vec2) {
+        if let Some(path) = self.session.take() {
+            match Session::load(&path) {
+                Ok(session) => {
+                    self.restore(ctx, &session, size);
+                    self.session = Some(path);
+                }
+                Err(e) => eprintln!("warning: {}, starting with the default layout and leaving the file as it is", e),
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut Context, media: &mut Media) {
+        self.draw(ctx, media);
+    }
+
+    fn shutdown(&mut self, _ctx: &mut Context) {
+        if let Some(ref path) = self.session {
+            if let Err(e) = self.save().save(path) {
+                eprintln!("warning: {}, the layout is not saved", e);
+            }
+        }
+    }
+}
+
+impl Entry {
+    /// Draws the panel in its window and notes where the window is.
+    fn show(&mut self, ctx: &mut Context, media: &mut Media) {
+        let mut scope = StyleScope::new(ctx);
+        scope.font(media.fonts.get(PANEL_FONT.0, PANEL_FONT.1));
+
+        let mut flags = self.panel.flags();
+        if !self.live && self.minimized {
+            flags |= nk_window_flags_NK_WINDOW_MINIMIZED as Flags;
+        }
+        // Without closable windows a window that is not drawn is minimized.
+        let expanded = scope.begin(::nuklear::String::from(self.panel.name()), self.bounds, flags);
+        self.bounds = scope.window_get_bounds();
+        self.minimized = !expanded;
+        self.live = true;
+        if expanded {
+            self.panel.draw(&mut scope, media);
+        }
+        scope.end();
+    }
+}
+
+/// `bounds` moved so that `GRIP` pixels of its title bar lie inside a UI of `size`.
+fn on_screen(bounds: Rect, size: Vec2) -> Rect {
+    let x = bounds.x.min(size.x - GRIP).max(GRIP.min(bounds.w) - bounds.w);
+    let y = bounds.y.min(size.y - GRIP).max(0f32);
+    Rect { x, y, ..bounds }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restored_windows_stay_on_screen() {
+        let size = Vec2 { x: 800f32, y: 600f32 };
+        let at = |x, y| on_screen(Rect { x, y, w: 200f32, h: 300f32 }, size);
+        let xy = |r: Rect| (r.x, r.y);
+        assert_eq!(xy(at(100f32, 50f32)), (100f32, 50f32));
+        assert_eq!(xy(at(2000f32, 1500f32)), (760f32, 560f32));
+        assert_eq!(xy(at(-500f32, -20f32)), (-160f32, 0f32));
+        assert_eq!(at(2000f32, 0f32).w, 200f32);
+    }
+}
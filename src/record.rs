@@ -0,0 +1,96 @@
+//! Capture of live input into a replayable script.
+
+use glutin;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use script::{Action, FRAME_MS};
+
+/// Appends every window event the UI consumes to a script file, stamped with its frame number,
+/// and the zoom changes, which the UI never sees as input.
+pub struct Recorder {
+    out: BufWriter<File>,
+    /// The zoom as of the script so far; replays start at 100%.
+    zoom: f64,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Recorder> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "# recorded session, {} ms frames; play back with --replay", FRAME_MS)?;
+        Ok(Recorder { out, zoom: 1.0 })
+    }
+
+    pub fn record(&mut self, frame: usize, event: &glutin::WindowEvent) -> io::Result<()> {
+        match Action::from_event(event) {
+            Some(action) => writeln!(self.out, "@{} {}", frame, action),
+            None => Ok(()),
+        }
+    }
+
+    /// Notes the zoom `frame` is drawn at, if it changed. Call after the frame's events.
+    pub fn zoom(&mut self, frame: usize, factor: f64) -> io::Result<()> {
+        if factor == self.zoom {
+            return Ok(());
+        }
+        self.zoom = factor;
+        writeln!(self.out, "@{} {}", frame, Action::Zoom(factor))
+    }
+
+    /// Flushes once per frame so a crash still leaves a complete reproduction behind.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glutin::{ElementState, MouseButton, VirtualKeyCode};
+
+    use std::env;
+    use std::fs;
+
+    use script::{self, Step};
+
+    #[test]
+    fn recordings_parse_back() {
+        let actions = vec![
+            Action::Resize(1024., 700.5),
+            Action::Move(191.25, 559.),
+            Action::Mouse(MouseButton::Left, ElementState::Pressed),
+            Action::Mouse(MouseButton::Left, ElementState::Released),
+            Action::Mouse(MouseButton::Other(8), ElementState::Pressed),
+            Action::Mouse(MouseButton::Other(8), ElementState::Released),
+            Action::Unfocus,
+            Action::Key(VirtualKeyCode::LControl, ElementState::Pressed),
+            Action::Key(VirtualKeyCode::A, ElementState::Released),
+            Action::Char('a'),
+            Action::Char(' '),
+            Action::Char('#'),
+            Action::Char('\u{8}'),
+            Action::Scroll(0., -1.5),
+        ];
+        let path = env::temp_dir().join(format!("nuklear-sample-record-{}.txt", ::std::process::id()));
+
+        let mut recorder = Recorder::create(&path).unwrap();
+        for (frame, action) in actions.iter().enumerate() {
+            recorder.record(frame, &action.to_event().unwrap()).unwrap();
+        }
+        recorder.zoom(20, 1.0).unwrap();
+        recorder.zoom(21, 1.25).unwrap();
+        recorder.zoom(22, 1.25).unwrap();
+        recorder.end_frame().unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let steps: Vec<(usize, Action)> = script::parse(&text).unwrap().into_iter().map(|Step { frame, action, .. }| (frame, action)).collect();
+
+        let mut expected: Vec<(usize, Action)> = actions.into_iter().enumerate().collect();
+        expected.push((21, Action::Zoom(1.25)));
+        assert_eq!(steps, expected);
+    }
+}
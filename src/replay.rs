@@ -0,0 +1,70 @@
+//! Deterministic replay of input scripts against the demo windows.
+//!
+//! The script (see `script`) is played back frame by frame into a headless UI running the demo
+//! panels, exactly as the live loop does; `resize` resizes the render target and `zoom` zooms the
+//! UI as the shortcuts do. `expect` steps compare panel state after their frame,
+//! by `key.field` paths (see `Panels::probe`); `clipboard` names the contents of the in-memory
+//! clipboard the replay runs with.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use headless::Headless;
+use script::{self, Action};
+use demos;
+use {HEADLESS_HEIGHT, HEADLESS_WIDTH};
+
+/// Plays `script_path` with the assets of `asset_root` and returns one message per failed
+/// expectation. When `out` is given the last frame is written there as a PNG.
+pub fn run(script_path: &Path, asset_root: &Path, out: Option<&Path>) -> Result<Vec<String>, String> {
+    let mut text = String::new();
+    File::open(script_path).and_then(|mut f| f.read_to_string(&mut text)).map_err(|e| format!("cannot read {}: {}", script_path.display(), e))?;
+    let steps = script::parse(&text).map_err(|e| format!("{}: {}", script_path.display(), e))?;
+
+    let mut headless = Headless::new(HEADLESS_WIDTH, HEADLESS_HEIGHT, asset_root)?;
+
+    let mut panels = demos::panels();
+
+    let mut failures = Vec::new();
+    let last_frame = steps.last().map_or(0, |s| s.frame);
+    let mut next = 0;
+
+    for frame in 0..=last_frame {
+        let start = next;
+        while next < steps.len() && steps[next].frame == frame {
+            next += 1;
+        }
+        let current = &steps[start..next];
+
+        for step in current {
+            if let Action::Zoom(factor) = step.action {
+                headless.set_zoom(factor);
+            }
+        }
+        let events: Vec<_> = current.iter().filter_map(|s| s.action.to_event()).collect();
+        headless.frame(&events, |ctx, media| panels.draw(ctx, media))?;
+
+        for step in current {
+            if let Action::Expect(ref path, ref expected) = step.action {
+                let actual = if path == "clipboard" {
+                    Some(headless.clipboard.contents().unwrap_or_default())
+                } else {
+                    panels.probe(path)
+                };
+                match actual {
+                    Some(ref actual) if actual == expected => (),
+                    Some(actual) => failures.push(format!("line {}: expected {} to be {}, got {}", step.line, path, expected, actual)),
+                    None => failures.push(format!("line {}: unknown state '{}'", step.line, path)),
+                }
+            }
+        }
+    }
+
+    if let Some(out) = out {
+        let frame = headless.read()?;
+        frame.save(out).map_err(|e| format!("cannot write {}: {}", out.display(), e))?;
+    }
+
+    Ok(failures)
+}
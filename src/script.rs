@@ -0,0 +1,321 @@
+//! Text format for recorded input.
+//!
+//! One step per line. `#` at the start of a line or after whitespace starts a comment, so values
+//! can hold it elsewhere (`expect grid.free a#b`, `expect grid.free "#"`, `char U+0023`). Every
+//! step begins with its time, either in milliseconds (`120`) or as a frame number (`@6`); steps
+//! with the same frame are applied in file order within that frame.
+//!
+//! ```text
+//! 0     move 120 340          # cursor position in logical pixels
+//! 20    press left            # left | middle | right | other N
+//! 40    release left
+//! @3    key down Back         # glutin VirtualKeyCode name
+//! @3    key up Back
+//! 60    char a                # or U+0020 for whitespace and control characters
+//! 80    scroll 0 -1           # wheel lines
+//! 100   resize 1280 800           # window size in logical pixels
+//! 110   zoom 1.25                 # UI zoom, as Ctrl with + or - sets it
+//! 115   unfocus                   # the window lost the keyboard focus
+//! 120   expect button.option 2
+//! 140   expect grid.hex ""        # one pair of quotes is stripped, so "" is the empty string
+//! 160   expect grid.free "a b"    # the value is the rest of the line
+//! ```
+
+use glutin;
+use glutin::dpi::{LogicalPosition, LogicalSize};
+use glutin::{ElementState, MouseButton, VirtualKeyCode};
+
+use std::fmt;
+
+/// Frame length the millisecond timestamps are quantized to; matches the live loop.
+pub const FRAME_MS: u64 = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Move(f64, f64),
+    Mouse(MouseButton, ElementState),
+    Key(VirtualKeyCode, ElementState),
+    Char(char),
+    Scroll(f32, f32),
+    Resize(f64, f64),
+    Zoom(f64),
+    /// The window lost the keyboard focus, letting go of the held keys.
+    Unfocus,
+    /// Checks a demo state value after the frame has run, e.g. `button.option 2`.
+    Expect(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub frame: usize,
+    pub line: usize,
+    pub action: Action,
+}
+
+pub fn parse(text: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let content = strip_comment(raw).trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        let (time, rest) = content.split_once(char::is_whitespace).unwrap_or((content, ""));
+        let frame = parse_time(time).ok_or_else(|| format!("line {}: bad time '{}'", line, time))?;
+        let action = parse_action(rest.trim_start()).map_err(|e| format!("line {}: {}", line, e))?;
+
+        if steps.last().is_some_and(|last: &Step| frame < last.frame) {
+            return Err(format!("line {}: time goes backwards", line));
+        }
+        steps.push(Step { frame, line, action });
+    }
+
+    Ok(steps)
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut after_space = true;
+    for (i, c) in line.char_indices() {
+        if c == '#' && after_space {
+            return &line[..i];
+        }
+        after_space = c.is_whitespace();
+    }
+    line
+}
+
+fn parse_time(s: &str) -> Option<usize> {
+    if let Some(frame) = s.strip_prefix('@') {
+        frame.parse().ok()
+    } else {
+        s.parse::<u64>().ok().map(|ms| (ms / FRAME_MS) as usize)
+    }
+}
+
+fn parse_action(text: &str) -> Result<Action, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let num = |i: usize| -> Result<f64, String> {
+        let w = words.get(i).ok_or_else(|| format!("'{}' needs {} arguments", words[0], i))?;
+        w.parse::<f64>().map_err(|_| format!("'{}' is not a number", w))
+    };
+
+    match words.first() {
+        Some(&"move") => Ok(Action::Move(num(1)?, num(2)?)),
+        Some(&"press") => Ok(Action::Mouse(parse_button(&words[1..])?, ElementState::Pressed)),
+        Some(&"release") => Ok(Action::Mouse(parse_button(&words[1..])?, ElementState::Released)),
+        Some(&"key") => {
+            let state = match words.get(1) {
+                Some(&"down") => ElementState::Pressed,
+                Some(&"up") => ElementState::Released,
+                _ => return Err("'key' needs 'down' or 'up'".into()),
+            };
+            let name = words.get(2).ok_or("'key' needs a key name")?;
+            let key = key_from_name(name).ok_or_else(|| format!("unknown key '{}'", name))?;
+            Ok(Action::Key(key, state))
+        }
+        Some(&"char") => parse_char(words.get(1).ok_or("'char' needs a character")?).map(Action::Char),
+        Some(&"scroll") => Ok(Action::Scroll(num(1)? as f32, num(2)? as f32)),
+        Some(&"resize") => match (num(1)?, num(2)?) {
+            (w, h) if w >= 1.0 && h >= 1.0 && w <= f64::from(u16::MAX) && h <= f64::from(u16::MAX) => Ok(Action::Resize(w, h)),
+            _ => Err(format!("'{} {}' is not a window size", words[1], words[2])),
+        },
+        Some(&"zoom") => match num(1)? {
+            z if z > 0.0 => Ok(Action::Zoom(z)),
+            _ => Err(format!("'{}' is not a zoom factor", words[1])),
+        },
+        Some(&"unfocus") => Ok(Action::Unfocus),
+        Some(&"expect") => {
+            let rest = text["expect".len()..].trim_start();
+            match rest.split_once(char::is_whitespace) {
+                Some((path, value)) => Ok(Action::Expect(path.to_string(), unquote(value.trim()).to_string())),
+                None => Err("'expect' needs a state path and a value".into()),
+            }
+        }
+        Some(other) => Err(format!("unknown action '{}'", other)),
+        None => Err("missing action".into()),
+    }
+}
+
+/// `value` without one pair of surrounding quotes, if it has them.
+fn unquote(value: &str) -> &str {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner,
+        None => value,
+    }
+}
+
+fn parse_button(words: &[&str]) -> Result<MouseButton, String> {
+    match *words {
+        ["left", ..] => Ok(MouseButton::Left),
+        ["middle", ..] => Ok(MouseButton::Middle),
+        ["right", ..] => Ok(MouseButton::Right),
+        ["other", n, ..] => n.parse().map(MouseButton::Other).map_err(|_| format!("bad mouse button number '{}'", n)),
+        ["other"] => Err("'other' needs a button number".into()),
+        [other, ..] => Err(format!("unknown mouse button '{}'", other)),
+        [] => Err("missing mouse button".into()),
+    }
+}
+
+fn parse_char(word: &str) -> Result<char, String> {
+    if let Some(hex) = word.strip_prefix("U+") {
+        u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32).ok_or_else(|| format!("bad code point '{}'", word))
+    } else {
+        let mut chars = word.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(format!("'{}' is not a single character", word)),
+        }
+    }
+}
+
+impl Action {
+    /// The step for a live window event; `None` for events the UI does not consume or keys the
+    /// format cannot name.
+    pub fn from_event(event: &glutin::WindowEvent) -> Option<Action> {
+        match *event {
+            glutin::WindowEvent::CursorMoved { position, .. } => Some(Action::Move(position.x, position.y)),
+            glutin::WindowEvent::MouseInput { state, button, .. } => Some(Action::Mouse(button, state)),
+            glutin::WindowEvent::MouseWheel {
+                delta: glutin::MouseScrollDelta::LineDelta(x, y),
+                ..
+            } => Some(Action::Scroll(x, y)),
+            glutin::WindowEvent::KeyboardInput {
+                input: glutin::KeyboardInput { state, virtual_keycode: Some(k), .. },
+                ..
+            } if key_from_name(&format!("{:?}", k)).is_some() => Some(Action::Key(k, state)),
+            glutin::WindowEvent::ReceivedCharacter(c) => Some(Action::Char(c)),
+            glutin::WindowEvent::Resized(size) => Some(Action::Resize(size.width, size.height)),
+            glutin::WindowEvent::Focused(false) => Some(Action::Unfocus),
+            _ => None,
+        }
+    }
+
+    /// The window event this step stands for; `None` for zoom changes and assertions.
+    pub fn to_event(&self) -> Option<glutin::WindowEvent> {
+        // Only ever read back by `InputTranslator`, never handed to winit.
+        let device_id = unsafe { glutin::DeviceId::dummy() };
+        let modifiers = glutin::ModifiersState::default();
+
+        Some(match *self {
+            Action::Move(x, y) => glutin::WindowEvent::CursorMoved {
+                device_id,
+                position: LogicalPosition::new(x, y),
+                modifiers,
+            },
+            Action::Mouse(button, state) => glutin::WindowEvent::MouseInput { device_id, state, button, modifiers },
+            Action::Key(key, state) => glutin::WindowEvent::KeyboardInput {
+                device_id,
+                input: glutin::KeyboardInput {
+                    scancode: 0,
+                    state,
+                    virtual_keycode: Some(key),
+                    modifiers,
+                },
+            },
+            Action::Char(c) => glutin::WindowEvent::ReceivedCharacter(c),
+            Action::Scroll(x, y) => glutin::WindowEvent::MouseWheel {
+                device_id,
+                delta: glutin::MouseScrollDelta::LineDelta(x, y),
+                phase: glutin::TouchPhase::Moved,
+                modifiers,
+            },
+            Action::Resize(w, h) => glutin::WindowEvent::Resized(LogicalSize::new(w, h)),
+            Action::Unfocus => glutin::WindowEvent::Focused(false),
+            Action::Zoom(_) | Action::Expect(..) => return None,
+        })
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let button = |b: &MouseButton| match *b {
+            MouseButton::Left => "left".to_string(),
+            MouseButton::Middle => "middle".to_string(),
+            MouseButton::Right => "right".to_string(),
+            MouseButton::Other(n) => format!("other {}", n),
+        };
+
+        match *self {
+            Action::Move(x, y) => write!(f, "move {} {}", x, y),
+            Action::Mouse(ref b, ElementState::Pressed) => write!(f, "press {}", button(b)),
+            Action::Mouse(ref b, ElementState::Released) => write!(f, "release {}", button(b)),
+            Action::Key(k, ElementState::Pressed) => write!(f, "key down {:?}", k),
+            Action::Key(k, ElementState::Released) => write!(f, "key up {:?}", k),
+            Action::Char(c) if c.is_whitespace() || c.is_control() || c == '#' => write!(f, "char U+{:04X}", c as u32),
+            Action::Char(c) => write!(f, "char {}", c),
+            Action::Scroll(x, y) => write!(f, "scroll {} {}", x, y),
+            Action::Resize(w, h) => write!(f, "resize {} {}", w, h),
+            Action::Zoom(z) => write!(f, "zoom {}", z),
+            Action::Unfocus => write!(f, "unfocus"),
+            Action::Expect(ref path, ref value) => write!(f, "expect {} \"{}\"", path, value),
+        }
+    }
+}
+
+macro_rules! key_names {
+    ($($key:ident),* $(,)*) => {
+        /// Looks a key up by its `VirtualKeyCode` variant name.
+        pub fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+            match name {
+                $(stringify!($key) => Some(VirtualKeyCode::$key),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+key_names!(
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, Insert, Home, Delete, End, PageDown, PageUp,
+    Left, Up, Right, Down, Back, Return, NumpadEnter, Space, Tab, LAlt, LControl, LShift, LWin, RAlt, RControl, RShift, RWin,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(line: &str) -> Result<Action, String> {
+        parse(line).map(|mut steps| steps.remove(0).action)
+    }
+
+    #[test]
+    fn expect_takes_the_rest_of_the_line() {
+        assert_eq!(action("0 expect grid.free \"hello world\""), Ok(Action::Expect("grid.free".into(), "hello world".into())));
+        assert_eq!(action("0 expect grid.free hello world"), Ok(Action::Expect("grid.free".into(), "hello world".into())));
+        assert_eq!(action("0 expect grid.hex \"\"  # empty"), Ok(Action::Expect("grid.hex".into(), "".into())));
+        assert_eq!(action("0 expect grid.hex \"\"\""), Ok(Action::Expect("grid.hex".into(), "\"".into())));
+        assert!(action("0 expect grid.hex").is_err());
+    }
+
+    #[test]
+    fn resize_and_zoom() {
+        assert_eq!(action("@2 resize 640 400"), Ok(Action::Resize(640., 400.)));
+        assert!(action("@2 resize 0 400").is_err());
+        assert!(action("@2 resize 640 70000").is_err());
+        assert_eq!(action("@2 zoom 1.25"), Ok(Action::Zoom(1.25)));
+        assert!(action("@2 zoom 0").is_err());
+        assert_eq!(Action::Zoom(1.25).to_event(), None);
+    }
+
+    #[test]
+    fn times_and_comments() {
+        let steps = parse("# a comment\n\n0 move 1 2\n39 press left # first frame\n@2 release left\n45 char U+0020\n").unwrap();
+        let frames: Vec<(usize, usize)> = steps.iter().map(|s| (s.frame, s.line)).collect();
+        assert_eq!(frames, vec![(0, 3), (1, 4), (2, 5), (2, 6)]);
+        assert_eq!(steps[3].action, Action::Char(' '));
+        assert_eq!(parse("@3 char a\n40 char b").unwrap_err(), "line 2: time goes backwards");
+
+        assert_eq!(parse("0 char #").unwrap_err(), "line 1: 'char' needs a character");
+        let steps = parse("0 expect grid.free a#b # the value\n0 expect grid.free \"#\"\t# quoted").unwrap();
+        assert_eq!(steps[0].action, Action::Expect("grid.free".into(), "a#b".into()));
+        assert_eq!(steps[1].action, Action::Expect("grid.free".into(), "#".into()));
+    }
+
+    #[test]
+    fn malformed_steps_are_errors() {
+        for line in &["move 1 2", "-20 move 1 2", "@x move 1 2", "0", "0 jump", "0 move 1", "0 move 1 x", "0 press thumb", "0 press other", "0 release other 256", "0 key down", "0 key sideways A", "0 key down Pause", "0 char ab", "0 char U+D800", "0 scroll"] {
+            assert!(parse(line).is_err(), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Window layout and panel state kept between runs.
+//!
+//! On exit the demo writes where each panel's window is, whether it is minimized or closed and
+//! whatever state the panel saves to a session file in the skin file format, one section per
+//! panel key; on startup it reads them back. The file carries a `version`: one written by another
+//! version is ignored as a whole rather than misread, and not overwritten on exit, while a
+//! missing key, section or `window` field only leaves that piece at its default, so files from
+//! before a panel gained state still load. Values of the wrong type count as missing.
+//!
+//! ```text
+//! version = 1
+//!
+//! [basic]
+//! window = { x = 320, y = 50, w = 275, h = 610, minimized = false, open = true }
+//! check0 = true
+//! selected_image = 3
+//! ```
+
+use nuklear::Rect;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use skin_file::{self, Entry, Value};
+
+/// The format version this build reads and writes.
+pub const VERSION: u32 = 1;
+
+/// Where the session is kept unless `--session` says otherwise: `nuklear-sample-extended/session.toml`
+/// under `$XDG_CONFIG_HOME`, or `~/.config` without it.
+pub fn default_path() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).filter(|dir| dir.is_absolute());
+    let config = config.or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("nuklear-sample-extended").join("session.toml"))
+}
+
+/// A value a panel can keep in its `State`.
+pub trait Field: Sized {
+    fn to_value(&self) -> Option<Value>;
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl Field for bool {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Bool(*self))
+    }
+
+    fn from_value(value: &Value) -> Option<bool> {
+        match *value {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl Field for f32 {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Number(*self))
+    }
+
+    fn from_value(value: &Value) -> Option<f32> {
+        match *value {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+/// Integers go through the file's `f32` numbers, exact up to 2^24. `MAX as f32` rounds up to a
+/// power of two past `MAX` for the wider types, so the upper bound is exclusive.
+macro_rules! integer_field {
+    ($($ty:ty),*) => {$(
+        impl Field for $ty {
+            fn to_value(&self) -> Option<Value> {
+                Some(Value::Number(*self as f32))
+            }
+
+            fn from_value(value: &Value) -> Option<$ty> {
+                match *value {
+                    Value::Number(n) if n.fract() == 0.0 && n >= <$ty>::MIN as f32 && n < <$ty>::MAX as f32 => Some(n as $ty),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+integer_field!(i32, u32, usize);
+
+impl Field for String {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Str(self.clone()))
+    }
+
+    fn from_value(value: &Value) -> Option<String> {
+        match *value {
+            Value::Str(ref s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// What a panel saves, by field name.
+#[derive(Debug, Default)]
+pub struct State {
+    fields: Vec<(String, Value)>,
+}
+
+impl State {
+    /// Keeps `value` under `key`, unless the file cannot hold it.
+    pub fn write<T: Field>(&mut self, key: &str, value: T) {
+        self.fields.retain(|(k, _)| k != key);
+        if let Some(value) = value.to_value() {
+            self.fields.push((key.to_string(), value));
+        }
+    }
+
+    /// The value under `key`, if there is one of the right type.
+    pub fn get<T: Field>(&self, key: &str) -> Option<T> {
+        self.fields.iter().find(|(k, _)| k == key).and_then(|(_, value)| T::from_value(value))
+    }
+
+    /// Overwrites `field` with the value under `key`, if there is one of the right type.
+    pub fn read<T: Field>(&self, key: &str, field: &mut T) {
+        if let Some(value) = self.get(key) {
+            *field = value;
+        }
+    }
+}
+
+/// A panel's window as it was left, each part absent when the file does not say.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Window {
+    pub bounds: Option<Rect>,
+    pub minimized: Option<bool>,
+    pub open: Option<bool>,
+}
+
+#[derive(Debug, Default)]
+pub struct Panel {
+    pub window: Window,
+    pub state: State,
+}
+
+/// The saved panels, by key.
+#[derive(Debug, Default)]
+pub struct Session {
+    panels: Vec<(String, Panel)>,
+}
+
+impl Session {
+    /// Reads the session at `path`. A missing file is an empty session, as on the first run.
+    pub fn load(path: &Path) -> Result<Session, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Session::default()),
+            Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+        };
+        let entries = skin_file::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        match entries.iter().find(|e| e.section.is_empty() && e.key == "version").map(|e| &e.value) {
+            None => (),
+            Some(&Value::Number(v)) if v == VERSION as f32 => (),
+            Some(v) => return Err(format!("{} has version {}, this build reads version {}", path.display(), v, VERSION)),
+        }
+
+        let mut session = Session::default();
+        for entry in entries.into_iter().filter(|e| !e.section.is_empty()) {
+            let panel = session.panel_mut(&entry.section);
+            match (entry.key.as_str(), entry.value) {
+                ("window", Value::Table(ref fields)) => panel.window = window(fields),
+                ("window", _) => (),
+                (_, value) => panel.state.fields.push((entry.key, value)),
+            }
+        }
+        Ok(session)
+    }
+
+    /// Writes the session to `path`, creating its directory.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut entries = vec![Entry { line: 0, section: String::new(), key: "version".into(), value: Value::Number(VERSION as f32) }];
+        for (key, panel) in &self.panels {
+            let entry = |k: &str, value| Entry { line: 0, section: key.clone(), key: k.to_string(), value };
+            let w = panel.window;
+            let mut fields = Vec::new();
+            if let Some(b) = w.bounds {
+                fields.extend(vec![("x", b.x), ("y", b.y), ("w", b.w), ("h", b.h)].into_iter().map(|(k, n)| (k.to_string(), Value::Number(n))));
+            }
+            fields.extend(w.minimized.map(|b| ("minimized".to_string(), Value::Bool(b))));
+            fields.extend(w.open.map(|b| ("open".to_string(), Value::Bool(b))));
+            entries.push(entry("window", Value::Table(fields)));
+            entries.extend(panel.state.fields.iter().map(|(k, value)| entry(k, value.clone())));
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+        }
+        fs::write(path, skin_file::write(&entries)).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+
+    pub fn panel(&self, key: &str) -> Option<&Panel> {
+        self.panels.iter().find(|(k, _)| k == key).map(|(_, panel)| panel)
+    }
+
+    /// The panel under `key`, added empty if the session has none.
+    pub fn panel_mut(&mut self, key: &str) -> &mut Panel {
+        match self.panels.iter().position(|(k, _)| k == key) {
+            Some(i) => &mut self.panels[i].1,
+            None => {
+                self.panels.push((key.to_string(), Panel::default()));
+                &mut self.panels.last_mut().unwrap().1
+            }
+        }
+    }
+}
+
+/// The window parts of an inline table; bounds count only when all four are there.
+fn window(fields: &[(String, Value)]) -> Window {
+    let get = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, value)| value);
+    let number = |key| get(key).and_then(f32::from_value);
+    let flag = |key| get(key).and_then(bool::from_value);
+
+    let bounds = match (number("x"), number("y"), number("w"), number("h")) {
+        (Some(x), Some(y), Some(w), Some(h)) => Some(Rect { x, y, w, h }),
+        _ => None,
+    };
+    Window { bounds, minimized: flag("minimized"), open: flag("open") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("nuklear-sample-session-{}-{}", ::std::process::id(), name)).join("session.toml")
+    }
+
+    #[test]
+    fn sessions_round_trip() {
+        let mut session = Session::default();
+        {
+            let panel = session.panel_mut("grid");
+            panel.window = Window { bounds: Some(Rect { x: 10., y: 20.5, w: 300., h: 400. }), minimized: Some(true), open: None };
+            panel.state.write("check", false);
+            panel.state.write("selected_item", 3usize);
+            panel.state.write("free", "a \"b\" \\ c\nd".to_string());
+        }
+        session.panel_mut("basic").window.open = Some(false);
+        let path = temp_path("round-trip");
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let grid = loaded.panel("grid").unwrap();
+        let bounds = grid.window.bounds.unwrap();
+        assert_eq!((bounds.x, bounds.y, bounds.w, bounds.h), (10., 20.5, 300., 400.));
+        assert_eq!((grid.window.minimized, grid.window.open), (Some(true), None));
+        assert_eq!(grid.state.get("check"), Some(false));
+        assert_eq!(grid.state.get("selected_item"), Some(3usize));
+        assert_eq!(grid.state.get("free"), Some("a \"b\" \\ c\nd".to_string()));
+        let basic = loaded.panel("basic").unwrap();
+        assert_eq!((basic.window.bounds.is_none(), basic.window.open), (true, Some(false)));
+    }
+
+    #[test]
+    fn missing_and_mistyped_values() {
+        let path = temp_path("partial");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "[grid]\nwindow = { x = 1, y = 2, w = 3, minimized = 1 }\ncheck = 1\nselected_item = -1\n").unwrap();
+        let loaded = Session::load(&path).unwrap();
+        fs::write(&path, "version = 99\n").unwrap();
+        let newer = Session::load(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let grid = loaded.panel("grid").unwrap();
+        assert!(grid.window.bounds.is_none() && grid.window.minimized.is_none());
+        assert_eq!(grid.state.get::<bool>("check"), None);
+        assert_eq!(grid.state.get::<usize>("selected_item"), None);
+        assert_eq!(grid.state.get::<f32>("selected_item"), Some(-1.));
+        assert!(newer.is_err());
+        assert!(Session::load(&temp_path("missing")).unwrap().panel("grid").is_none());
+
+        assert_eq!(i32::from_value(&Value::Number(2147483648.0)), None);
+        assert_eq!(i32::from_value(&Value::Number(-2147483648.0)), Some(i32::MIN));
+        assert_eq!(u32::from_value(&Value::Number(4294967296.0)), None);
+    }
+}
@@ -0,0 +1,381 @@
+//! Image skins for the Nuklear style, after the upstream "skinning" example.
+//!
+//! A skin file (see `skin_file`) names an atlas image and sets style properties section by
+//! section; image states are sub-rectangles of the atlas. `Skin::load` uploads the atlas and checks
+//! every entry against a scratch style, so a broken skin is rejected at startup with the offending
+//! line instead of being half applied. An image state with a `margin` is drawn nine-sliced (see
+//! `Drawer::nine_slice`), so its corners keep their size however large the widget is.
+
+use gfx;
+use image;
+use nuklear::nuklear_sys::{nk_style, nk_style_combo, nk_style_property, nk_style_scrollbar, nk_style_slider, nk_style_tab, nk_style_text, nk_style_window};
+use nuklear::{color_rgba, Color, Context, Image, Style, StyleButton, StyleCombo, StyleEdit, StyleItem, StyleProgress, StyleProperty, StyleScrollbar, StyleSelectable, StyleSlider, StyleTab, StyleToggle, StyleWindow, StyleWindowHeader, SymbolType, Vec2};
+
+use std::fs;
+use std::path::Path;
+
+use assets::AssetError;
+use drawer::{Drawer, Texture};
+use skin_file::{self, Entry, Value};
+
+/// The bundled skin, relative to the asset root.
+pub const GWEN: &str = "skins/gwen.toml";
+
+struct Atlas {
+    texture: Texture,
+    width: u16,
+    height: u16,
+    /// The atlas nine-sliced with each margin the skin uses.
+    sliced: Vec<([f32; 4], Texture)>,
+}
+
+/// A parsed skin whose atlas is uploaded to the drawer. The atlas is freed with the skin, so keep it
+/// for as long as the style it was applied to is in use.
+pub struct Skin {
+    atlas: Atlas,
+    entries: Vec<Entry>,
+}
+
+impl Skin {
+    pub fn load<F, R: gfx::Resources>(factory: &mut F, drawer: &mut Drawer<R>, path: &Path) -> Result<Skin, AssetError>
+    where
+        F: gfx::Factory<R>,
+    {
+        let skin_error = |message| AssetError::Skin { path: path.to_path_buf(), message };
+        let text = fs::read_to_string(path).map_err(|e| AssetError::io(path, e))?;
+        let entries = skin_file::parse(&text).map_err(skin_error)?;
+
+        let mut atlas_path = None;
+        for entry in entries.iter().filter(|e| e.section.is_empty()) {
+            match (entry.key.as_str(), &entry.value) {
+                ("atlas", Value::Str(file)) => atlas_path = Some(path.parent().unwrap_or_else(|| Path::new("")).join(file)),
+                ("atlas", other) => return Err(skin_error(format!("line {}: atlas: expected a file name, found {}", entry.line, other))),
+                (key, _) => return Err(skin_error(format!("line {}: unknown setting '{}'", entry.line, key))),
+            }
+        }
+        let atlas_path = atlas_path.ok_or_else(|| skin_error("missing 'atlas = \"<image>\"'".to_string()))?;
+
+        let data = fs::read(&atlas_path).map_err(|e| AssetError::io(&atlas_path, e))?;
+        let img = image::load_from_memory(&data).map_err(|error| AssetError::Decode { path: atlas_path.clone(), error })?.to_rgba();
+        let (width, height) = img.dimensions();
+        let texture = drawer.add_texture(factory, &img, width, height, &atlas_path.to_string_lossy());
+
+        // Margins that are not whole pixels get no texture; checking the entries reports them.
+        let mut sliced: Vec<([f32; 4], Texture)> = Vec::new();
+        for entry in &entries {
+            if let Value::Table(ref fields) = entry.value {
+                if let Ok((_, margin)) = image_fields(fields) {
+                    let whole = margin.iter().all(|&m| m >= 0. && m <= f32::from(u16::MAX) && m.fract() == 0.);
+                    if whole && margin != [0.; 4] && !sliced.iter().any(|s| s.0 == margin) {
+                        let texture = drawer.nine_slice(&texture, [margin[0] as u16, margin[1] as u16, margin[2] as u16, margin[3] as u16]);
+                        sliced.push((margin, texture));
+                    }
+                }
+            }
+        }
+
+        let skin = Skin {
+            atlas: Atlas { texture, width: width as u16, height: height as u16, sliced },
+            entries: entries.into_iter().filter(|e| !e.section.is_empty()).collect(),
+        };
+        let mut scratch = nk_style::default();
+        skin.apply_to(scratch.as_mut()).map_err(skin_error)?;
+        Ok(skin)
+    }
+
+    /// Sets every property the skin mentions on the context style, leaving the others alone.
+    pub fn apply(&self, ctx: &mut Context) {
+        self.apply_to(ctx.style_mut()).expect("skin is checked when loaded");
+    }
+
+    fn apply_to(&self, style: &mut Style) -> Result<(), String> {
+        for entry in &self.entries {
+            let field = Field { value: &entry.value, atlas: &self.atlas };
+            apply_entry(style, &entry.section, &entry.key, &field).map_err(|e| format!("line {}: [{}] {}: {}", entry.line, entry.section, entry.key, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A property value, converted to the type its setter takes.
+struct Field<'a> {
+    value: &'a Value,
+    atlas: &'a Atlas,
+}
+
+impl<'a> Field<'a> {
+    fn float(&self) -> Result<f32, String> {
+        match *self.value {
+            Value::Number(n) => Ok(n),
+            ref other => Err(format!("expected a number, found {}", other)),
+        }
+    }
+
+    fn flag(&self) -> Result<bool, String> {
+        match *self.value {
+            Value::Bool(b) => Ok(b),
+            ref other => Err(format!("expected true or false, found {}", other)),
+        }
+    }
+
+    fn vec2(&self) -> Result<Vec2, String> {
+        match *self.value {
+            Value::Array(ref a) if a.len() == 2 => Ok(Vec2 { x: a[0], y: a[1] }),
+            ref other => Err(format!("expected [x, y], found {}", other)),
+        }
+    }
+
+    fn color(&self) -> Result<Color, String> {
+        match *self.value {
+            Value::Str(ref s) => parse_color(s),
+            ref other => Err(format!("expected a color like \"#rrggbb\", found {}", other)),
+        }
+    }
+
+    fn symbol(&self) -> Result<SymbolType, String> {
+        let name = match *self.value {
+            Value::Str(ref s) => s.as_str(),
+            ref other => return Err(format!("expected a symbol name, found {}", other)),
+        };
+        Ok(match name {
+            "none" => SymbolType::None,
+            "x" => SymbolType::X,
+            "underscore" => SymbolType::Underscore,
+            "circle_solid" => SymbolType::CircleSolid,
+            "circle_outline" => SymbolType::CircleOutline,
+            "rect_solid" => SymbolType::RectSolid,
+            "rect_outline" => SymbolType::RectOutline,
+            "triangle_up" => SymbolType::TriangleUp,
+            "triangle_down" => SymbolType::TriangleDown,
+            "triangle_left" => SymbolType::TriangleLeft,
+            "triangle_right" => SymbolType::TriangleRight,
+            "plus" => SymbolType::Plus,
+            "minus" => SymbolType::Minus,
+            _ => return Err(format!("unknown symbol '{}'", name)),
+        })
+    }
+
+    /// `"hide"`, a color, or `{ image = [x, y, w, h], margin = [left, top, right, bottom] }`.
+    fn item(&self) -> Result<StyleItem, String> {
+        match *self.value {
+            Value::Str(ref s) if s == "hide" => Ok(StyleItem::hide()),
+            Value::Str(ref s) => parse_color(s).map(StyleItem::color),
+            Value::Table(ref fields) => {
+                let (rect, margin) = image_fields(fields)?;
+                self.slice(rect, margin).map(StyleItem::image)
+            }
+            ref other => Err(format!("expected \"hide\", a color or an image, found {}", other)),
+        }
+    }
+
+    fn slice(&self, rect: [f32; 4], margin: [f32; 4]) -> Result<Image, String> {
+        let [x, y, w, h] = rect;
+        if rect.iter().chain(margin.iter()).any(|&v| v < 0. || v.fract() != 0.) {
+            return Err("image rectangles and margins are whole, non-negative pixels".into());
+        }
+        if w == 0. || h == 0. || x + w > f32::from(self.atlas.width) || y + h > f32::from(self.atlas.height) {
+            return Err(format!("image {:?} is not inside the {}x{} atlas", rect, self.atlas.width, self.atlas.height));
+        }
+        if margin[0] + margin[2] >= w || margin[1] + margin[3] >= h {
+            return Err(format!("margin {:?} leaves nothing of the {}x{} image", margin, w, h));
+        }
+
+        let texture = self.atlas.sliced.iter().find(|s| s.0 == margin).map_or(&self.atlas.texture, |s| &s.1);
+        let mut image = texture.image();
+        {
+            let raw = image.as_mut();
+            raw.w = self.atlas.width;
+            raw.h = self.atlas.height;
+            raw.region = [x as u16, y as u16, w as u16, h as u16];
+        }
+        Ok(image)
+    }
+}
+
+/// The rectangle and margin of an image state, the margin zero unless given.
+fn image_fields(fields: &[(::std::string::String, Value)]) -> Result<([f32; 4], [f32; 4]), String> {
+    let mut rect = None;
+    let mut margin = [0.; 4];
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("image", Value::Array(a)) if a.len() == 4 => rect = Some([a[0], a[1], a[2], a[3]]),
+            ("margin", Value::Array(a)) if a.len() == 4 => margin = [a[0], a[1], a[2], a[3]],
+            ("image", _) | ("margin", _) => return Err(format!("'{}' takes four numbers", key)),
+            _ => return Err(format!("unknown image field '{}'", key)),
+        }
+    }
+    let rect = rect.ok_or("image states need 'image = [x, y, w, h]'")?;
+    Ok((rect, margin))
+}
+
+fn parse_color(s: &str) -> Result<Color, String> {
+    let hex = s.strip_prefix('#').filter(|h| (h.len() == 6 || h.len() == 8) && h.chars().all(|c| c.is_ascii_hexdigit()));
+    let hex = hex.ok_or_else(|| format!("'{}' is not a color like \"#rrggbb\" or \"#rrggbbaa\"", s))?;
+    let channel = |i: usize| hex.get(i * 2..i * 2 + 2).map_or(255, |c| i32::from_str_radix(c, 16).unwrap());
+    Ok(color_rgba(channel(0), channel(1), channel(2), channel(3)))
+}
+
+/// Calls the setter named `set_<key>` with the value converted by its `Field` method.
+macro_rules! properties {
+    ($target:expr, $key:expr, $field:expr; $($setter:ident($kind:ident)),* $(,)*) => {{
+        $(
+            if $key == &stringify!($setter)[4..] {
+                $target.$setter($field.$kind()?);
+                return Ok(());
+            }
+        )*
+        Err("unknown property".into())
+    }};
+}
+
+fn apply_entry(style: &mut Style, section: &str, key: &str, f: &Field) -> Result<(), String> {
+    match section {
+        "text" => text(style.text_mut(), key, f),
+        "window" => window(style.window_mut(), key, f),
+        "window.header" => header(AsMut::<nk_style_window>::as_mut(style.window_mut()).header.as_mut(), key, f),
+        "button" => button(style.button_mut(), key, f),
+        "contextual_button" => button(style.contextual_button_mut(), key, f),
+        "menu_button" => button(style.menu_button_mut(), key, f),
+        "checkbox" => toggle(style.checkbox_mut(), key, f),
+        "option" => toggle(style.option_mut(), key, f),
+        "selectable" => selectable(style.selectable_mut(), key, f),
+        "slider" => slider(style.slider_mut(), key, f),
+        "slider.inc_button" => button(AsMut::<nk_style_slider>::as_mut(style.slider_mut()).inc_button.as_mut(), key, f),
+        "slider.dec_button" => button(AsMut::<nk_style_slider>::as_mut(style.slider_mut()).dec_button.as_mut(), key, f),
+        "progress" => progress(style.progress_mut(), key, f),
+        "property" => property(style.property_mut(), key, f),
+        "property.inc_button" => button(AsMut::<nk_style_property>::as_mut(style.property_mut()).inc_button.as_mut(), key, f),
+        "property.dec_button" => button(AsMut::<nk_style_property>::as_mut(style.property_mut()).dec_button.as_mut(), key, f),
+        "edit" => edit(style.edit_mut(), key, f),
+        "combo" => combo(style.combo_mut(), key, f),
+        "combo.button" => button(AsMut::<nk_style_combo>::as_mut(style.combo_mut()).button.as_mut(), key, f),
+        "scroll_h" => scrollbar(style.scroll_h_mut(), key, f),
+        "scroll_h.inc_button" => button(AsMut::<nk_style_scrollbar>::as_mut(style.scroll_h_mut()).inc_button.as_mut(), key, f),
+        "scroll_h.dec_button" => button(AsMut::<nk_style_scrollbar>::as_mut(style.scroll_h_mut()).dec_button.as_mut(), key, f),
+        "scroll_v" => scrollbar(style.scroll_v_mut(), key, f),
+        "scroll_v.inc_button" => button(AsMut::<nk_style_scrollbar>::as_mut(style.scroll_v_mut()).inc_button.as_mut(), key, f),
+        "scroll_v.dec_button" => button(AsMut::<nk_style_scrollbar>::as_mut(style.scroll_v_mut()).dec_button.as_mut(), key, f),
+        "tab" => tab(style.tab_mut(), key, f),
+        "tab.tab_minimize_button" => button(AsMut::<nk_style_tab>::as_mut(style.tab_mut()).tab_minimize_button.as_mut(), key, f),
+        "tab.tab_maximize_button" => button(AsMut::<nk_style_tab>::as_mut(style.tab_mut()).tab_maximize_button.as_mut(), key, f),
+        "tab.node_minimize_button" => button(AsMut::<nk_style_tab>::as_mut(style.tab_mut()).node_minimize_button.as_mut(), key, f),
+        "tab.node_maximize_button" => button(AsMut::<nk_style_tab>::as_mut(style.tab_mut()).node_maximize_button.as_mut(), key, f),
+        _ => Err("unknown section".into()),
+    }
+}
+
+fn text(t: &mut nk_style_text, key: &str, f: &Field) -> Result<(), String> {
+    match key {
+        "color" => t.color = f.color()?,
+        "padding" => t.padding = f.vec2()?,
+        _ => return Err("unknown property".into()),
+    }
+    Ok(())
+}
+
+fn window(w: &mut StyleWindow, key: &str, f: &Field) -> Result<(), String> {
+    properties!(w, key, f;
+        set_background(color), set_fixed_background(item), set_scaler(item),
+        set_border_color(color), set_popup_border_color(color), set_combo_border_color(color), set_contextual_border_color(color),
+        set_menu_border_color(color), set_group_border_color(color), set_tooltip_border_color(color),
+        set_border(float), set_combo_border(float), set_contextual_border(float), set_menu_border(float), set_group_border(float),
+        set_tooltip_border(float), set_popup_border(float), set_rounding(float),
+        set_spacing(vec2), set_scrollbar_size(vec2), set_min_size(vec2), set_padding(vec2), set_group_padding(vec2),
+        set_popup_padding(vec2), set_combo_padding(vec2), set_contextual_padding(vec2), set_menu_padding(vec2), set_tooltip_padding(vec2),
+    )
+}
+
+fn header(h: &mut StyleWindowHeader, key: &str, f: &Field) -> Result<(), String> {
+    properties!(h, key, f;
+        set_normal(item), set_hover(item), set_active(item),
+        set_close_symbol(symbol), set_minimize_symbol(symbol), set_maximize_symbol(symbol),
+        set_label_normal(color), set_label_hover(color), set_label_active(color),
+        set_padding(vec2), set_label_padding(vec2), set_spacing(vec2),
+    )
+}
+
+fn button(b: &mut StyleButton, key: &str, f: &Field) -> Result<(), String> {
+    properties!(b, key, f;
+        set_normal(item), set_hover(item), set_active(item),
+        set_border_color(color), set_text_background(color), set_text_normal(color), set_text_hover(color), set_text_active(color),
+        set_border(float), set_rounding(float), set_padding(vec2), set_touch_padding(vec2), set_image_padding(vec2),
+    )
+}
+
+fn toggle(t: &mut StyleToggle, key: &str, f: &Field) -> Result<(), String> {
+    properties!(t, key, f;
+        set_normal(item), set_hover(item), set_active(item), set_cursor_normal(item), set_cursor_hover(item),
+        set_border_color(color), set_text_background(color), set_text_normal(color), set_text_hover(color), set_text_active(color),
+        set_spacing(float), set_border(float), set_padding(vec2), set_touch_padding(vec2),
+    )
+}
+
+fn selectable(s: &mut StyleSelectable, key: &str, f: &Field) -> Result<(), String> {
+    properties!(s, key, f;
+        set_normal(item), set_hover(item), set_pressed(item), set_normal_active(item), set_hover_active(item), set_pressed_active(item),
+        set_text_normal(color), set_text_hover(color), set_text_pressed(color), set_text_normal_active(color), set_text_hover_active(color),
+        set_text_pressed_active(color), set_text_background(color),
+        set_rounding(float), set_padding(vec2), set_touch_padding(vec2), set_image_padding(vec2),
+    )
+}
+
+fn slider(s: &mut StyleSlider, key: &str, f: &Field) -> Result<(), String> {
+    properties!(s, key, f;
+        set_normal(item), set_hover(item), set_active(item), set_cursor_normal(item), set_cursor_hover(item), set_cursor_active(item),
+        set_border_color(color), set_bar_normal(color), set_bar_hover(color), set_bar_active(color), set_bar_filled(color),
+        set_border(float), set_rounding(float), set_bar_height(float), set_padding(vec2), set_spacing(vec2), set_cursor_size(vec2),
+        set_show_buttons(flag), set_inc_symbol(symbol), set_dec_symbol(symbol),
+    )
+}
+
+fn progress(p: &mut StyleProgress, key: &str, f: &Field) -> Result<(), String> {
+    properties!(p, key, f;
+        set_normal(item), set_hover(item), set_active(item), set_cursor_normal(item), set_cursor_hover(item), set_cursor_active(item),
+        set_border_color(color), set_cursor_border_color(color),
+        set_border(float), set_rounding(float), set_cursor_border(float), set_cursor_rounding(float), set_padding(vec2),
+    )
+}
+
+fn property(p: &mut StyleProperty, key: &str, f: &Field) -> Result<(), String> {
+    properties!(p, key, f;
+        set_normal(item), set_hover(item), set_active(item),
+        set_border_color(color), set_label_normal(color), set_label_hover(color), set_label_active(color),
+        set_sym_left(symbol), set_sym_right(symbol), set_border(float), set_rounding(float), set_padding(vec2),
+    )
+}
+
+fn edit(e: &mut StyleEdit, key: &str, f: &Field) -> Result<(), String> {
+    properties!(e, key, f;
+        set_normal(item), set_hover(item), set_active(item),
+        set_border_color(color), set_cursor_normal(color), set_cursor_hover(color), set_cursor_text_normal(color), set_cursor_text_hover(color),
+        set_text_normal(color), set_text_hover(color), set_text_active(color),
+        set_selected_normal(color), set_selected_hover(color), set_selected_text_normal(color), set_selected_text_hover(color),
+        set_border(float), set_rounding(float), set_cursor_size(float), set_scrollbar_size(vec2), set_padding(vec2), set_row_padding(float),
+    )
+}
+
+fn combo(c: &mut StyleCombo, key: &str, f: &Field) -> Result<(), String> {
+    properties!(c, key, f;
+        set_normal(item), set_hover(item), set_active(item),
+        set_border_color(color), set_label_normal(color), set_label_hover(color), set_label_active(color),
+        set_symbol_normal(color), set_symbol_hover(color), set_symbol_active(color),
+        set_sym_normal(symbol), set_sym_hover(symbol), set_sym_active(symbol),
+        set_border(float), set_rounding(float), set_content_padding(vec2), set_button_padding(vec2), set_spacing(vec2),
+    )
+}
+
+fn scrollbar(s: &mut StyleScrollbar, key: &str, f: &Field) -> Result<(), String> {
+    properties!(s, key, f;
+        set_normal(item), set_hover(item), set_active(item), set_cursor_normal(item), set_cursor_hover(item), set_cursor_active(item),
+        set_border_color(color), set_cursor_border_color(color),
+        set_border(float), set_rounding(float), set_border_cursor(float), set_rounding_cursor(float), set_padding(vec2),
+        set_show_buttons(flag), set_inc_symbol(symbol), set_dec_symbol(symbol),
+    )
+}
+
+fn tab(t: &mut StyleTab, key: &str, f: &Field) -> Result<(), String> {
+    properties!(t, key, f;
+        set_background(item), set_border_color(color), set_text(color), set_sym_minimize(symbol), set_sym_maximize(symbol),
+        set_border(float), set_rounding(float), set_indent(float), set_padding(vec2), set_spacing(vec2),
+    )
+}
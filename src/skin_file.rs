@@ -0,0 +1,280 @@
+//! Text format for skin definitions, the asset manifest and the session file, a subset of TOML.
+//!
+//! Top-level keys describe the skin as a whole, `[section]` headers name a style part such as
+//! `button` or `scroll_v.inc_button`, and the keys below them set its properties. Values are
+//! numbers, booleans, strings, arrays of numbers and one-line inline tables of those. Strings
+//! escape `\`, `"` and line breaks with a backslash, as `\\`, `\"`, `\n` and `\r`.
+//!
+//! ```text
+//! atlas = "gwen.png"                  # relative to the skin file
+//!
+//! [button]
+//! normal = { image = [384, 336, 127, 31], margin = [8, 8, 8, 8] }
+//! hover = "#e0e0e0"                   # a color fills the whole widget
+//! active = "hide"                     # draws nothing
+//! text_normal = "#5f5f5f"
+//! rounding = 2
+//! padding = [4, 4]
+//! ```
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f32),
+    Bool(bool),
+    Str(String),
+    Array(Vec<f32>),
+    Table(Vec<(String, Value)>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Str(ref s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    match c {
+                        '\\' => f.write_str("\\\\")?,
+                        '"' => f.write_str("\\\"")?,
+                        '\n' => f.write_str("\\n")?,
+                        '\r' => f.write_str("\\r")?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Value::Array(ref a) => write!(f, "{:?}", a),
+            Value::Table(_) => write!(f, "an inline table"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub line: usize,
+    /// Empty for top-level keys.
+    pub section: String,
+    pub key: String,
+    pub value: Value,
+}
+
+pub fn parse(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut section = String::new();
+
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let content = strip_comment(raw).trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        if content.starts_with('[') {
+            let name = content.strip_prefix('[').and_then(|c| c.strip_suffix(']')).map(str::trim).ok_or_else(|| format!("line {}: unterminated section header", line))?;
+            if name.is_empty() || !name.split('.').all(is_key) {
+                return Err(format!("line {}: bad section name '{}'", line, name));
+            }
+            section = name.to_string();
+            continue;
+        }
+
+        let eq = content.find('=').ok_or_else(|| format!("line {}: expected 'key = value'", line))?;
+        let key = content[..eq].trim();
+        if !is_key(key) {
+            return Err(format!("line {}: bad key '{}'", line, key));
+        }
+        if let Some(first) = entries.iter().find(|e| e.section == section && e.key == key) {
+            return Err(format!("line {}: '{}' is already set on line {}", line, key, first.line));
+        }
+
+        let mut cursor = Cursor { text: content[eq + 1..].trim() };
+        let value = cursor.value().map_err(|e| format!("line {}: {}", line, e))?;
+        if !cursor.text.trim().is_empty() {
+            return Err(format!("line {}: unexpected '{}' after the value", line, cursor.text.trim()));
+        }
+
+        entries.push(Entry { line, section: section.clone(), key: key.to_string(), value });
+    }
+
+    Ok(entries)
+}
+
+/// Writes `entries` in the syntax `parse` reads, with a section header wherever the section
+/// changes, so entries of one section belong together.
+pub fn write(entries: &[Entry]) -> String {
+    let mut text = String::new();
+    let mut section = "";
+    for entry in entries {
+        if entry.section != section {
+            section = &entry.section;
+            text.push_str(&format!("\n[{}]\n", section));
+        }
+        text.push_str(&format!("{} = {}\n", entry.key, write_value(&entry.value)));
+    }
+    text
+}
+
+fn write_value(value: &Value) -> String {
+    match *value {
+        Value::Array(ref items) => format!("[{}]", items.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")),
+        Value::Table(ref fields) => format!("{{ {} }}", fields.iter().map(|(k, v)| format!("{} = {}", k, write_value(v))).collect::<Vec<_>>().join(", ")),
+        ref value => value.to_string(),
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let (mut in_string, mut escaped) = (false, false);
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn is_key(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+struct Cursor<'a> {
+    text: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn skip_space(&mut self) {
+        self.text = self.text.trim_start();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+        if self.text.starts_with(c) {
+            self.text = &self.text[c.len_utf8()..];
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_space();
+        if self.eat('"') {
+            let mut s = String::new();
+            let mut chars = self.text.char_indices();
+            loop {
+                match chars.next() {
+                    Some((i, '"')) => {
+                        self.text = &self.text[i + 1..];
+                        return Ok(Value::Str(s));
+                    }
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, '\\')) => s.push('\\'),
+                        Some((_, '"')) => s.push('"'),
+                        Some((_, 'n')) => s.push('\n'),
+                        Some((_, 'r')) => s.push('\r'),
+                        Some((_, c)) => return Err(format!("unknown escape '\\{}' in string", c)),
+                        None => return Err("unterminated string".into()),
+                    },
+                    Some((_, c)) => s.push(c),
+                    None => return Err("unterminated string".into()),
+                }
+            }
+        } else if self.eat('[') {
+            let mut items = Vec::new();
+            while !self.eat(']') {
+                if !items.is_empty() && !self.eat(',') {
+                    return Err("expected ',' or ']' in array".into());
+                }
+                if self.eat(']') {
+                    break;
+                }
+                match self.value()? {
+                    Value::Number(n) => items.push(n),
+                    other => return Err(format!("arrays hold numbers only, found {}", other)),
+                }
+            }
+            Ok(Value::Array(items))
+        } else if self.eat('{') {
+            let mut fields: Vec<(String, Value)> = Vec::new();
+            while !self.eat('}') {
+                if !fields.is_empty() && !self.eat(',') {
+                    return Err("expected ',' or '}' in inline table".into());
+                }
+                self.skip_space();
+                let end = self.text.find('=').ok_or("expected 'key = value' in inline table")?;
+                let key = self.text[..end].trim();
+                if !is_key(key) {
+                    return Err(format!("bad key '{}' in inline table", key));
+                }
+                if fields.iter().any(|(k, _)| k == key) {
+                    return Err(format!("'{}' is set twice in inline table", key));
+                }
+                let key = key.to_string();
+                self.text = &self.text[end + 1..];
+                match self.value()? {
+                    Value::Table(_) => return Err("inline tables cannot nest".into()),
+                    value => fields.push((key, value)),
+                }
+            }
+            Ok(Value::Table(fields))
+        } else {
+            let end = self.text.find(|c: char| c == ',' || c == ']' || c == '}' || c.is_whitespace()).unwrap_or(self.text.len());
+            let word = &self.text[..end];
+            self.text = &self.text[end..];
+            match word {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "" => Err("missing value".into()),
+                _ => word.parse().map(Value::Number).map_err(|_| format!("'{}' is not a value", word)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_round_trip() {
+        let text = "a \"quoted\" \\path\\ # not a comment\nsecond line\r";
+        let entries = vec![Entry { line: 1, section: String::new(), key: "s".into(), value: Value::Str(text.into()) }];
+        let written = write(&entries);
+        assert_eq!(written.lines().count(), 1);
+        assert_eq!(parse(&written).unwrap(), entries);
+        assert_eq!(parse(r#"s = "a\"b" # "c"#).unwrap()[0].value, Value::Str("a\"b".into()));
+        assert!(parse(r#"s = "a\tb""#).is_err());
+        assert!(parse(r#"s = "a\"#).is_err());
+    }
+
+    #[test]
+    fn sections_and_values() {
+        let text = "top = 1.5 # comment\n\n[button]\nnormal = { image = [1, 2, 3, 4], hide = false }\n[scroll_v.inc_button]\npadding = [4, 4,]\n";
+        let entries = parse(text).unwrap();
+        let summary: Vec<(usize, &str, &str)> = entries.iter().map(|e| (e.line, e.section.as_str(), e.key.as_str())).collect();
+        assert_eq!(summary, vec![(1, "", "top"), (4, "button", "normal"), (6, "scroll_v.inc_button", "padding")]);
+        assert_eq!(entries[0].value, Value::Number(1.5));
+        assert_eq!(entries[1].value, Value::Table(vec![("image".into(), Value::Array(vec![1., 2., 3., 4.])), ("hide".into(), Value::Bool(false))]));
+        assert_eq!(entries[2].value, Value::Array(vec![4., 4.]));
+        assert_eq!(parse(&write(&entries)).unwrap().into_iter().map(|e| e.value).collect::<Vec<_>>(), entries.into_iter().map(|e| e.value).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn malformed_lines_are_errors() {
+        let lines = ["[button", "[]", "[a..b]", "key", "bad key = 1", "a = 1\na = 2", "a = 1 2", "a = [1, \"x\"]", "a = { b = { c = 1 } }", "a = { b = 1, b = 2 }", "a = [1 2]", "a =", "a = yes"];
+        for text in &lines {
+            assert!(parse(text).is_err(), "{}", text);
+        }
+        assert_eq!(parse("x = 1\n[s]\nx = 2").unwrap().len(), 2);
+        assert!(parse("\n\n[s]\nx = 2\nx = 3").unwrap_err().starts_with("line 5:"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Scoped style changes on top of Nuklear's style stacks.
+//!
+//! Nuklear can push a new value for a style field and pop the old one back later, but a forgotten
+//! pop leaks the change into everything drawn after it, other windows included. A `StyleScope`
+//! remembers what it pushed and pops it when dropped, restoring the exact previous values. It
+//! dereferences to the context, so widgets are drawn through the scope while it lives.
+//!
+//! ```ignore
+//! let mut ctx = StyleScope::new(ctx);
+//! ctx.vec2(|s| &mut s.window.padding, Vec2 { x: 0., y: 0. }).float(|s| &mut s.button.rounding, 0.).font(small);
+//! ctx.button_text("Compact");
+//! ```
+
+use nuklear::nuklear_sys::{nk_context, nk_flags, nk_style, nk_style_item, nk_user_font};
+use nuklear::nuklear_sys::{nk_style_pop_color, nk_style_pop_flags, nk_style_pop_float, nk_style_pop_font, nk_style_pop_style_item, nk_style_pop_vec2};
+use nuklear::nuklear_sys::{nk_style_push_color, nk_style_push_flags, nk_style_push_float, nk_style_push_font, nk_style_push_style_item, nk_style_push_vec2};
+use nuklear::{Color, Context, Flags, StyleItem, UserFont, Vec2};
+
+use std::ops::{Deref, DerefMut};
+
+use raw_context;
+
+#[derive(Debug, Clone, Copy)]
+enum Stack {
+    Font,
+    Float,
+    Vec2,
+    Item,
+    Flags,
+    Color,
+}
+
+pub struct StyleScope<'a> {
+    ctx: &'a mut Context,
+    pushed: Vec<Stack>,
+}
+
+impl<'a> StyleScope<'a> {
+    pub fn new(ctx: &'a mut Context) -> StyleScope<'a> {
+        StyleScope { ctx, pushed: Vec::new() }
+    }
+
+    /// Draws text with `font` until the scope ends. Nuklear keeps a pointer to the font, so it
+    /// must outlive the scope.
+    pub fn font(&mut self, font: &UserFont) -> &mut Self {
+        let pushed = unsafe { nk_style_push_font(self.raw(), AsRef::<nk_user_font>::as_ref(font)) };
+        self.record(pushed, Stack::Font)
+    }
+
+    /// Sets a float field such as `window.rounding` or `button.border`.
+    pub fn float<F: FnOnce(&mut nk_style) -> &mut f32>(&mut self, field: F, value: f32) -> &mut Self {
+        let pushed = unsafe {
+            let ctx = self.raw();
+            nk_style_push_float(ctx, field(&mut (*ctx).style), value)
+        };
+        self.record(pushed, Stack::Float)
+    }
+
+    pub fn vec2<F: FnOnce(&mut nk_style) -> &mut Vec2>(&mut self, field: F, value: Vec2) -> &mut Self {
+        let pushed = unsafe {
+            let ctx = self.raw();
+            nk_style_push_vec2(ctx, field(&mut (*ctx).style), value)
+        };
+        self.record(pushed, Stack::Vec2)
+    }
+
+    pub fn item<F: FnOnce(&mut nk_style) -> &mut nk_style_item>(&mut self, field: F, value: StyleItem) -> &mut Self {
+        let pushed = unsafe {
+            let ctx = self.raw();
+            nk_style_push_style_item(ctx, field(&mut (*ctx).style), *value.as_ref())
+        };
+        self.record(pushed, Stack::Item)
+    }
+
+    /// Sets a flags field such as `button.text_alignment`.
+    pub fn flags<F: FnOnce(&mut nk_style) -> &mut nk_flags>(&mut self, field: F, value: Flags) -> &mut Self {
+        let pushed = unsafe {
+            let ctx = self.raw();
+            nk_style_push_flags(ctx, field(&mut (*ctx).style), value)
+        };
+        self.record(pushed, Stack::Flags)
+    }
+
+    pub fn color<F: FnOnce(&mut nk_style) -> &mut Color>(&mut self, field: F, value: Color) -> &mut Self {
+        let pushed = unsafe {
+            let ctx = self.raw();
+            nk_style_push_color(ctx, field(&mut (*ctx).style), value)
+        };
+        self.record(pushed, Stack::Color)
+    }
+
+    // The wrapper's push methods want the field and the context borrowed mutably at once, so the
+    // scope goes through the C API.
+    fn raw(&mut self) -> *mut nk_context {
+        raw_context(self.ctx)
+    }
+
+    fn record(&mut self, pushed: i32, stack: Stack) -> &mut Self {
+        // Nuklear refuses the push when that stack is full and there is nothing to pop later.
+        if pushed != 0 {
+            self.pushed.push(stack);
+        } else {
+            eprintln!("style {:?} stack is full, change ignored", stack);
+        }
+        self
+    }
+}
+
+impl<'a> Deref for StyleScope<'a> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        self.ctx
+    }
+}
+
+impl<'a> DerefMut for StyleScope<'a> {
+    fn deref_mut(&mut self) -> &mut Context {
+        self.ctx
+    }
+}
+
+impl<'a> Drop for StyleScope<'a> {
+    fn drop(&mut self) {
+        let ctx = self.raw();
+        while let Some(stack) = self.pushed.pop() {
+            unsafe {
+                match stack {
+                    Stack::Font => nk_style_pop_font(ctx),
+                    Stack::Float => nk_style_pop_float(ctx),
+                    Stack::Vec2 => nk_style_pop_vec2(ctx),
+                    Stack::Item => nk_style_pop_style_item(ctx),
+                    Stack::Flags => nk_style_pop_flags(ctx),
+                    Stack::Color => nk_style_pop_color(ctx),
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Rasterization of SVG icons, with `resvg`.
+//!
+//! Text is not drawn, as no fonts are loaded for it, and neither are embedded raster images.
+
+use image::RgbaImage;
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{Options, Tree, TreeParsing};
+
+/// Pixels on the longer side of a rasterized icon, at most.
+const MAX_SIDE: u32 = 4096;
+
+/// Rasterizes the SVG document `text`. `size` is the length of its longer side in logical pixels,
+/// its own size if `None`, and `scale` the pixels per logical pixel.
+pub fn rasterize(text: &str, size: Option<u32>, scale: f64) -> Result<RgbaImage, String> {
+    let tree = Tree::from_str(text, &Options::default()).map_err(|e| e.to_string())?;
+    let (width, height) = (tree.size.width(), tree.size.height());
+
+    let logical = size.map_or(1.0, |s| s as f32 / width.max(height));
+    let pixels = |side: f32| ((side * logical) as f64 * scale).round().max(1.0);
+    let (w, h) = (pixels(width), pixels(height));
+    if w > f64::from(MAX_SIDE) || h > f64::from(MAX_SIDE) {
+        return Err(format!("{}x{} pixels is larger than {} a side", w, h, MAX_SIDE));
+    }
+    let (w, h) = (w as u32, h as u32);
+
+    let mut pixmap = Pixmap::new(w, h).ok_or_else(|| format!("cannot allocate {}x{} pixels", w, h))?;
+    let fit = Transform::from_scale(w as f32 / width, h as f32 / height);
+    resvg::Tree::from_usvg(&tree).render(fit, &mut pixmap.as_mut());
+
+    // tiny-skia keeps premultiplied alpha, the textures straight alpha.
+    let straight = pixmap.pixels().iter().flat_map(|p| {
+        let c = p.demultiply();
+        [c.red(), c.green(), c.blue(), c.alpha()]
+    });
+    Ok(RgbaImage::from_raw(w, h, straight.collect()).expect("a pixmap has 4 bytes a pixel"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const XMLNS: &str = "xmlns='http://www.w3.org/2000/svg'";
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        let documents = [
+            "",
+            "<g/>",
+            "<svg>",
+            "<svg width='10' height='10'><!-- ééééé",
+        ];
+        for document in &documents {
+            assert!(rasterize(document, None, 1.0).is_err(), "{}", document);
+        }
+        for size in &["width='100000' height='10'", "width='1e39' height='1'"] {
+            assert!(rasterize(&format!("<svg {} {}/>", XMLNS, size), None, 1.0).is_err(), "{}", size);
+        }
+        assert!(rasterize(&format!("<svg {} width='10' height='10'/>", XMLNS), Some(10_000), 1.0).is_err());
+    }
+
+    #[test]
+    fn fills_shapes() {
+        let image = rasterize(&format!("<svg {} viewBox='0 0 4 4'><rect x='1' y='1' width='2' height='2' fill='red'/></svg>", XMLNS), Some(4), 1.0).unwrap();
+        assert_eq!(image.dimensions(), (4, 4));
+        assert_eq!(*image.get_pixel(1, 1), Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+
+        let image = rasterize(&format!("<svg {} width='8' height='4'><circle cx='2' cy='2' r='2' fill='#00f' fill-opacity='0.5'/></svg>", XMLNS), None, 2.0).unwrap();
+        assert_eq!(image.dimensions(), (16, 8));
+        assert_eq!(*image.get_pixel(4, 4), Rgba([0, 0, 255, 128]));
+        assert_eq!(image.get_pixel(12, 4)[3], 0);
+    }
+}
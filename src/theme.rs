@@ -0,0 +1,182 @@
+//! Built-in color themes, the tables of upstream Nuklear's `demo/style.c`.
+//!
+//! A theme rebuilds the whole context style from a color table, so it replaces any skin or earlier
+//! theme. Fonts are kept.
+
+use nuklear::{color_rgba, ColorMap, Context, StyleColor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Default,
+    Dark,
+    White,
+    Red,
+    Blue,
+}
+
+/// One RGBA color per `StyleColor`, in its order: text, window, header, border, button (normal,
+/// hover, active), toggle (normal, hover, cursor), select (normal, active), slider (bar, cursor
+/// normal, hover, active), property, edit, edit cursor, combo, chart (background, color,
+/// highlight), scrollbar (track, cursor normal, hover, active) and tab header.
+type ColorTable = [[u8; 4]; 28];
+
+const DARK: ColorTable = [
+    [210, 210, 210, 255],
+    [57, 67, 71, 215],
+    [51, 51, 56, 220],
+    [46, 46, 46, 255],
+    [48, 83, 111, 255],
+    [58, 93, 121, 255],
+    [63, 98, 126, 255],
+    [50, 58, 61, 255],
+    [45, 53, 56, 255],
+    [48, 83, 111, 255],
+    [57, 67, 61, 255],
+    [48, 83, 111, 255],
+    [50, 58, 61, 255],
+    [48, 83, 111, 245],
+    [53, 88, 116, 255],
+    [58, 93, 121, 255],
+    [50, 58, 61, 255],
+    [50, 58, 61, 225],
+    [210, 210, 210, 255],
+    [50, 58, 61, 255],
+    [50, 58, 61, 255],
+    [48, 83, 111, 255],
+    [255, 0, 0, 255],
+    [50, 58, 61, 255],
+    [48, 83, 111, 255],
+    [53, 88, 116, 255],
+    [58, 93, 121, 255],
+    [48, 83, 111, 255],
+];
+
+const WHITE: ColorTable = [
+    [70, 70, 70, 255],
+    [175, 175, 175, 255],
+    [175, 175, 175, 255],
+    [0, 0, 0, 255],
+    [185, 185, 185, 255],
+    [170, 170, 170, 255],
+    [160, 160, 160, 255],
+    [150, 150, 150, 255],
+    [120, 120, 120, 255],
+    [175, 175, 175, 255],
+    [190, 190, 190, 255],
+    [175, 175, 175, 255],
+    [190, 190, 190, 255],
+    [80, 80, 80, 255],
+    [70, 70, 70, 255],
+    [60, 60, 60, 255],
+    [175, 175, 175, 255],
+    [150, 150, 150, 255],
+    [0, 0, 0, 255],
+    [175, 175, 175, 255],
+    [160, 160, 160, 255],
+    [45, 45, 45, 255],
+    [255, 0, 0, 255],
+    [180, 180, 180, 255],
+    [140, 140, 140, 255],
+    [150, 150, 150, 255],
+    [160, 160, 160, 255],
+    [180, 180, 180, 255],
+];
+
+const RED: ColorTable = [
+    [190, 190, 190, 255],
+    [30, 33, 40, 215],
+    [181, 45, 69, 220],
+    [51, 55, 67, 255],
+    [181, 45, 69, 255],
+    [190, 50, 70, 255],
+    [195, 55, 75, 255],
+    [51, 55, 67, 255],
+    [45, 60, 60, 255],
+    [181, 45, 69, 255],
+    [51, 55, 67, 255],
+    [181, 45, 69, 255],
+    [51, 55, 67, 255],
+    [181, 45, 69, 255],
+    [186, 50, 74, 255],
+    [191, 55, 79, 255],
+    [51, 55, 67, 255],
+    [51, 55, 67, 225],
+    [190, 190, 190, 255],
+    [51, 55, 67, 255],
+    [51, 55, 67, 255],
+    [170, 40, 60, 255],
+    [255, 0, 0, 255],
+    [30, 33, 40, 255],
+    [64, 84, 95, 255],
+    [70, 90, 100, 255],
+    [75, 95, 105, 255],
+    [181, 45, 69, 220],
+];
+
+const BLUE: ColorTable = [
+    [20, 20, 20, 255],
+    [202, 212, 214, 215],
+    [137, 182, 224, 220],
+    [140, 159, 173, 255],
+    [137, 182, 224, 255],
+    [142, 187, 229, 255],
+    [147, 192, 234, 255],
+    [177, 210, 210, 255],
+    [182, 215, 215, 255],
+    [137, 182, 224, 255],
+    [177, 210, 210, 255],
+    [137, 182, 224, 255],
+    [177, 210, 210, 255],
+    [137, 182, 224, 245],
+    [142, 188, 229, 255],
+    [147, 193, 234, 255],
+    [210, 210, 210, 255],
+    [210, 210, 210, 225],
+    [20, 20, 20, 255],
+    [210, 210, 210, 255],
+    [210, 210, 210, 255],
+    [137, 182, 224, 255],
+    [255, 0, 0, 255],
+    [190, 200, 200, 255],
+    [64, 84, 95, 255],
+    [70, 90, 100, 255],
+    [75, 95, 105, 255],
+    [156, 193, 220, 255],
+];
+
+impl Theme {
+    pub const ALL: [Theme; 5] = [Theme::Default, Theme::Dark, Theme::White, Theme::Red, Theme::Blue];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Default => "Default",
+            Theme::Dark => "Dark",
+            Theme::White => "White",
+            Theme::Red => "Red",
+            Theme::Blue => "Blue",
+        }
+    }
+
+    fn table(self) -> Option<&'static ColorTable> {
+        match self {
+            Theme::Default => None,
+            Theme::Dark => Some(&DARK),
+            Theme::White => Some(&WHITE),
+            Theme::Red => Some(&RED),
+            Theme::Blue => Some(&BLUE),
+        }
+    }
+
+    pub fn apply(self, ctx: &mut Context) {
+        match self.table() {
+            None => ctx.style_default(),
+            Some(table) => {
+                let mut colors = ColorMap::default();
+                for (i, &[r, g, b, a]) in table.iter().enumerate() {
+                    colors.set(i as StyleColor, color_rgba(i32::from(r), i32::from(g), i32::from(b), i32::from(a)));
+                }
+                ctx.style_from_table(&colors);
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! UI zoom for the demo window.
+//!
+//! Zooming scales the whole UI on top of the hidpi factor: the drawer draws at the combined scale,
+//! the font atlas is rebaked for it so text stays sharp, and the layout space shrinks by the zoom so
+//! widgets keep their logical sizes. `Ctrl` with `+` or `-` steps through `STEPS`, `Ctrl+0` resets.
+
+use glutin;
+
+/// Zoom factors the shortcuts step through.
+pub const STEPS: [f64; 11] = [0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0];
+
+pub struct Zoom {
+    factor: f64,
+}
+
+impl Zoom {
+    /// Starts at `factor`, which need not be one of the steps.
+    pub fn new(factor: f64) -> Zoom {
+        assert!(factor > 0.0, "zoom must be positive");
+        Zoom { factor }
+    }
+
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    /// Applies a zoom shortcut as its key goes down. Returns whether the factor changed, which
+    /// it does not at either end of `STEPS`.
+    pub fn handle_key(&mut self, input: &glutin::KeyboardInput) -> bool {
+        let next = match shortcut(input) {
+            Some(_) if input.state == glutin::ElementState::Released => return false,
+            Some(Shortcut::In) => STEPS.iter().cloned().find(|&s| s > self.factor + 1e-3),
+            Some(Shortcut::Out) => STEPS.iter().rev().cloned().find(|&s| s < self.factor - 1e-3),
+            Some(Shortcut::Reset) => Some(1.0),
+            None => return false,
+        };
+        let next = next.unwrap_or(self.factor);
+        let changed = next != self.factor;
+        self.factor = next;
+        changed
+    }
+}
+
+enum Shortcut {
+    In,
+    Out,
+    Reset,
+}
+
+fn shortcut(input: &glutin::KeyboardInput) -> Option<Shortcut> {
+    use glutin::VirtualKeyCode as V;
+
+    match *input {
+        glutin::KeyboardInput { virtual_keycode: Some(k), modifiers, .. } if modifiers.ctrl => match k {
+            V::Equals | V::Add | V::NumpadEquals => Some(Shortcut::In),
+            V::Minus | V::Subtract => Some(Shortcut::Out),
+            V::Key0 | V::Numpad0 => Some(Shortcut::Reset),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `input` is a zoom shortcut going down or up, which the UI should not see either way.
+pub fn is_shortcut(input: &glutin::KeyboardInput) -> bool {
+    shortcut(input).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glutin::{ElementState, KeyboardInput, ModifiersState, VirtualKeyCode};
+
+    fn key(k: VirtualKeyCode, state: ElementState, ctrl: bool) -> KeyboardInput {
+        let modifiers = ModifiersState { ctrl, ..Default::default() };
+        KeyboardInput { scancode: 0, state, virtual_keycode: Some(k), modifiers }
+    }
+
+    #[test]
+    fn changes_only_on_key_down() {
+        let mut zoom = Zoom::new(1.0);
+        assert!(zoom.handle_key(&key(VirtualKeyCode::Equals, ElementState::Pressed, true)));
+        assert_eq!(zoom.factor(), 1.1);
+        assert!(!zoom.handle_key(&key(VirtualKeyCode::Equals, ElementState::Released, true)));
+        assert_eq!(zoom.factor(), 1.1);
+        assert!(is_shortcut(&key(VirtualKeyCode::Equals, ElementState::Released, true)));
+        assert!(zoom.handle_key(&key(VirtualKeyCode::Key0, ElementState::Pressed, true)));
+        assert_eq!(zoom.factor(), 1.0);
+        assert!(!zoom.handle_key(&key(VirtualKeyCode::Key0, ElementState::Pressed, true)));
+    }
+
+    #[test]
+    fn stops_at_the_ends() {
+        let mut zoom = Zoom::new(1.9);
+        assert!(zoom.handle_key(&key(VirtualKeyCode::Add, ElementState::Pressed, true)));
+        assert_eq!(zoom.factor(), 2.0);
+        assert!(!zoom.handle_key(&key(VirtualKeyCode::Add, ElementState::Pressed, true)));
+        assert_eq!(zoom.factor(), 2.0);
+
+        let mut zoom = Zoom::new(0.5);
+        assert!(!zoom.handle_key(&key(VirtualKeyCode::Minus, ElementState::Pressed, true)));
+        assert_eq!(zoom.factor(), 0.5);
+    }
+
+    #[test]
+    fn needs_ctrl() {
+        let mut zoom = Zoom::new(1.0);
+        assert!(!is_shortcut(&key(VirtualKeyCode::Equals, ElementState::Pressed, false)));
+        assert!(!zoom.handle_key(&key(VirtualKeyCode::Equals, ElementState::Pressed, false)));
+        assert!(!is_shortcut(&key(VirtualKeyCode::A, ElementState::Pressed, true)));
+    }
+}
@@ -0,0 +1,52 @@
+//! The golden-image check and the replay scripts, run headless as `--golden` and `--replay` do.
+//! Without OSMesa there is nothing to render with, and the tests are skipped with a notice.
+
+extern crate nuklear_sample_extended;
+
+use nuklear_sample_extended::{golden, headless, replay};
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const ASSET_ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res");
+
+/// Whether a software GL context can be created; says why not on stderr otherwise.
+fn can_render(test: &str) -> bool {
+    match headless::create_context(16, 16) {
+        Ok(_) => true,
+        Err(e) => {
+            // Straight to stderr, which the test harness does not capture, so the skip is seen.
+            let _ = writeln!(io::stderr(), "skipping {}: {}", test, e);
+            false
+        }
+    }
+}
+
+#[test]
+fn demos_match_their_golden_images() {
+    if !can_render("the golden check") {
+        return;
+    }
+    let opts = golden::Options {
+        asset_root: PathBuf::from(ASSET_ROOT),
+        out_dir: PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/target/golden")),
+        ..Default::default()
+    };
+    let failures = golden::run(&opts).unwrap();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn replay_scripts_pass() {
+    if !can_render("the replay scripts") {
+        return;
+    }
+    let mut scripts: Vec<PathBuf> = fs::read_dir(Path::new(ASSET_ROOT).join("replay")).unwrap().map(|entry| entry.unwrap().path()).collect();
+    scripts.sort();
+    assert!(!scripts.is_empty());
+    for script in scripts {
+        let failures = replay::run(&script, Path::new(ASSET_ROOT), None).unwrap_or_else(|e| panic!("{}: {}", script.display(), e));
+        assert!(failures.is_empty(), "{}: {}", script.display(), failures.join("\n"));
+    }
+}